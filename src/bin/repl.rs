@@ -0,0 +1,250 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use chess::{play_command, ChessBoard, GameStatus, Glyphs, PgnGame, Player, SearchLimits, Searcher, Undo};
+
+const HELP: &str = "Commands:
+  Move E2,E4 | e2e4 | Nf3   play a move in coordinate, UCI or algebraic notation
+  go [depth]                let the computer play a move for the side to move
+  undo                      take back the last move
+  resign                    the side to move resigns
+  draw                      claim a draw, offer one, or accept the opponent's offer
+  flip                      turn the board around
+  glyphs                    switch between letters and chess symbols
+  fen                       print the position in FEN
+  pgn                       print the game so far in PGN
+  new                       start a new game
+  help                      show this message
+  quit                      leave";
+
+/// How many plies deep the computer looks when no depth is given.
+const DEFAULT_DEPTH: u32 = 4;
+
+struct Game {
+    chess_board: ChessBoard,
+    start: ChessBoard,
+    undos: Vec<Undo>,
+    flipped: bool,
+    glyphs: Glyphs,
+    draw_offer: Option<Player>,
+    /// Set when the game ended by resignation or agreement rather than on the board.
+    outcome: Option<(&'static str, String)>,
+    /// Kept for the whole game so that each search can reuse the positions stored by the last.
+    searcher: Searcher,
+}
+
+impl Game {
+    fn new() -> Self {
+        Game {
+            chess_board: ChessBoard::new(),
+            start: ChessBoard::new(),
+            undos: Vec::new(),
+            flipped: false,
+            glyphs: Glyphs::Ascii,
+            draw_offer: None,
+            outcome: None,
+            searcher: Searcher::new(),
+        }
+    }
+
+    fn result(&self) -> &'static str {
+        match self.outcome {
+            Some((result, _)) => result,
+            None => self.chess_board.status().result(),
+        }
+    }
+
+    fn is_over(&self) -> bool {
+        self.outcome.is_some() || self.chess_board.status().is_over()
+    }
+
+    fn pgn(&self) -> String {
+        let mut game = PgnGame::new(self.start.clone());
+        for undo in &self.undos {
+            game.push(undo.chess_move()).expect("moves played in the REPL are legal");
+        }
+        game.set_tag("Result", self.result());
+        game.to_pgn()
+    }
+
+    fn play(&mut self, command: &str) -> Result<(), String> {
+        if let Some((_, description)) = &self.outcome {
+            return Err(format!("the game is already over, {}", description));
+        }
+        let mover = self.chess_board.turn();
+        let undo = play_command(command, &mut self.chess_board).map_err(|error| error.to_string())?;
+        self.undos.push(undo);
+        if self.draw_offer == Some(mover.opponent()) {
+            println!("{} declines the draw offer", side(mover));
+            self.draw_offer = None;
+        }
+        Ok(())
+    }
+
+    fn computer_move(&mut self, depth: u32) -> Result<(), String> {
+        if self.is_over() {
+            return Err("the game is already over".to_string());
+        }
+        let limits = SearchLimits {
+            depth: Some(depth),
+            ..SearchLimits::default()
+        };
+        let result = self.searcher.search(&self.chess_board, &limits, |_| ());
+        let chess_move = result.best_move.ok_or_else(|| "there is no move to play".to_string())?;
+        let score = match result.mate_in() {
+            Some(moves) => format!("mate in {}", moves),
+            None => format!("{:+.2}", result.score as f64 / 100.0),
+        };
+        println!(
+            "{} plays {} ({}, depth {}, {} nodes)",
+            side(self.chess_board.turn()),
+            self.chess_board.to_san(chess_move),
+            score,
+            result.depth,
+            result.nodes
+        );
+        self.play(&chess_move.to_uci())
+    }
+
+    fn undo(&mut self) -> Result<(), String> {
+        match self.undos.pop() {
+            Some(undo) => {
+                self.chess_board.unmake_move(undo);
+                self.outcome = None;
+                self.draw_offer = None;
+                Ok(())
+            }
+            None => Err("there is no move to take back".to_string()),
+        }
+    }
+
+    fn resign(&mut self) -> Result<(), String> {
+        if self.is_over() {
+            return Err("the game is already over".to_string());
+        }
+        let loser = self.chess_board.turn();
+        let result = match loser {
+            Player::White => "0-1",
+            Player::Black => "1-0",
+        };
+        self.outcome = Some((result, format!("{} resigns, {} wins", side(loser), side(loser.opponent()))));
+        Ok(())
+    }
+
+    fn draw(&mut self) -> Result<(), String> {
+        if self.is_over() {
+            return Err("the game is already over".to_string());
+        }
+        let player = self.chess_board.turn();
+        if let GameStatus::DrawClaimable(reason) = self.chess_board.status() {
+            self.outcome = Some(("1/2-1/2", format!("{} claims a draw by {}", side(player), reason)));
+        } else if self.draw_offer == Some(player.opponent()) {
+            self.outcome = Some(("1/2-1/2", "draw agreed".to_string()));
+        } else if self.draw_offer == Some(player) {
+            return Err(format!("{} has already offered a draw", side(player)));
+        } else {
+            self.draw_offer = Some(player);
+            println!("{} offers a draw, {} may type draw to accept", side(player), side(player.opponent()));
+        }
+        Ok(())
+    }
+
+    fn announce(&self) {
+        let orientation = if self.flipped { Player::Black } else { Player::White };
+        let view = self
+            .chess_board
+            .view()
+            .colour(io::stdout().is_terminal())
+            .glyphs(self.glyphs)
+            .orientation(orientation)
+            .last_move(self.undos.last().map(Undo::chess_move));
+        println!("\n{}", view);
+        match &self.outcome {
+            Some((result, description)) => println!("{} ({})", description, result),
+            None => {
+                let status = self.chess_board.status();
+                if status.is_over() {
+                    println!("{} ({})", status, status.result());
+                } else {
+                    if self.chess_board.in_check(self.chess_board.turn()) {
+                        println!("{} is in check", side(self.chess_board.turn()));
+                    }
+                    if let GameStatus::DrawClaimable(reason) = status {
+                        println!("a draw can be claimed by {}", reason);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn side(player: Player) -> &'static str {
+    match player {
+        Player::White => "White",
+        Player::Black => "Black",
+    }
+}
+
+fn main() {
+    let mut game = Game::new();
+    println!("Type help for the list of commands.");
+    game.announce();
+
+    let stdin = io::stdin();
+    loop {
+        print!("{} to move> ", side(game.chess_board.turn()));
+        io::stdout().flush().ok();
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => (),
+        }
+        let command = line.trim();
+        let outcome = match command.to_lowercase().as_str() {
+            "" => continue,
+            "quit" | "exit" => break,
+            "help" => {
+                println!("{}", HELP);
+                continue;
+            }
+            "fen" => {
+                println!("{}", game.chess_board.to_fen());
+                continue;
+            }
+            "pgn" => {
+                print!("{}", game.pgn());
+                continue;
+            }
+            "flip" => {
+                game.flipped = !game.flipped;
+                Ok(())
+            }
+            "glyphs" => {
+                game.glyphs = match game.glyphs {
+                    Glyphs::Ascii => Glyphs::Unicode,
+                    Glyphs::Unicode => Glyphs::Ascii,
+                };
+                Ok(())
+            }
+            "new" => {
+                let (flipped, glyphs) = (game.flipped, game.glyphs);
+                game = Game::new();
+                game.flipped = flipped;
+                game.glyphs = glyphs;
+                Ok(())
+            }
+            "undo" => game.undo(),
+            "go" => game.computer_move(DEFAULT_DEPTH),
+            _ if command.to_lowercase().starts_with("go ") => match command[3..].trim().parse() {
+                Ok(depth) if depth > 0 => game.computer_move(depth),
+                _ => Err(format!("'{}' is not a search depth", command[3..].trim())),
+            },
+            "resign" => game.resign(),
+            "draw" => game.draw(),
+            _ => game.play(command),
+        };
+        match outcome {
+            Ok(()) => game.announce(),
+            Err(why) => println!("error: {}", why),
+        }
+    }
+}
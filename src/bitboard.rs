@@ -0,0 +1,159 @@
+use crate::{ChessPiece, Piece, Player, Square};
+
+/// A set of squares, one bit per square index.
+pub(crate) type Bitboard = u64;
+
+pub(crate) const fn bit(square: usize) -> Bitboard {
+    1 << square
+}
+
+/// Iterates over the squares in a set from a1 upwards.
+pub(crate) struct Squares(Bitboard);
+
+impl Iterator for Squares {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.0 == 0 {
+            return None;
+        }
+        let square = self.0.trailing_zeros() as usize;
+        self.0 &= self.0 - 1;
+        Some(square)
+    }
+}
+
+pub(crate) fn squares(bitboard: Bitboard) -> Squares {
+    Squares(bitboard)
+}
+
+/// The squares reached from `square` by each (rank, file) step that stays on the board.
+const fn step_attacks(steps: &[(i32, i32)]) -> [Bitboard; 64] {
+    let mut table = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let mut i = 0;
+        while i < steps.len() {
+            let row = (square / 8) as i32 + steps[i].0;
+            let col = (square % 8) as i32 + steps[i].1;
+            if row >= 0 && row < 8 && col >= 0 && col < 8 {
+                table[square] |= bit((row * 8 + col) as usize);
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    table
+}
+
+pub(crate) const KNIGHT_ATTACKS: [Bitboard; 64] =
+    step_attacks(&[(2, 1), (2, -1), (1, 2), (1, -2), (-1, 2), (-1, -2), (-2, 1), (-2, -1)]);
+pub(crate) const KING_ATTACKS: [Bitboard; 64] =
+    step_attacks(&[(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)]);
+/// The squares a pawn on each square captures on, indexed by `Player as usize`.
+pub(crate) const PAWN_ATTACKS: [[Bitboard; 64]; 2] = [step_attacks(&[(-1, 1), (-1, -1)]), step_attacks(&[(1, 1), (1, -1)])];
+
+/// Ray directions as (rank, file) steps. The first four run towards higher square indices, so
+/// the nearest blocker on them is the lowest set bit; on the last four it is the highest.
+const DIRECTIONS: [(i32, i32); 8] = [(1, 0), (0, 1), (1, 1), (1, -1), (-1, 0), (0, -1), (-1, -1), (-1, 1)];
+const NORTH: usize = 0;
+const EAST: usize = 1;
+const NORTH_EAST: usize = 2;
+const NORTH_WEST: usize = 3;
+const SOUTH: usize = 4;
+const WEST: usize = 5;
+const SOUTH_WEST: usize = 6;
+const SOUTH_EAST: usize = 7;
+
+/// Every square from `square` to the edge of the board in each direction, excluding `square`.
+const fn rays() -> [[Bitboard; 64]; 8] {
+    let mut table = [[0; 64]; 8];
+    let mut direction = 0;
+    while direction < 8 {
+        let mut square = 0;
+        while square < 64 {
+            let mut row = (square / 8) as i32 + DIRECTIONS[direction].0;
+            let mut col = (square % 8) as i32 + DIRECTIONS[direction].1;
+            while row >= 0 && row < 8 && col >= 0 && col < 8 {
+                table[direction][square] |= bit((row * 8 + col) as usize);
+                row += DIRECTIONS[direction].0;
+                col += DIRECTIONS[direction].1;
+            }
+            square += 1;
+        }
+        direction += 1;
+    }
+    table
+}
+
+const RAYS: [[Bitboard; 64]; 8] = rays();
+
+/// The ray from `square` in `direction`, cut off after the first occupied square.
+fn ray_attacks(square: usize, direction: usize, occupied: Bitboard) -> Bitboard {
+    let ray = RAYS[direction][square];
+    let blockers = ray & occupied;
+    if blockers == 0 {
+        return ray;
+    }
+    let blocker = if direction < SOUTH {
+        blockers.trailing_zeros() as usize
+    } else {
+        63 - blockers.leading_zeros() as usize
+    };
+    ray ^ RAYS[direction][blocker]
+}
+
+pub(crate) fn bishop_attacks(square: usize, occupied: Bitboard) -> Bitboard {
+    ray_attacks(square, NORTH_EAST, occupied)
+        | ray_attacks(square, NORTH_WEST, occupied)
+        | ray_attacks(square, SOUTH_EAST, occupied)
+        | ray_attacks(square, SOUTH_WEST, occupied)
+}
+
+pub(crate) fn rook_attacks(square: usize, occupied: Bitboard) -> Bitboard {
+    ray_attacks(square, NORTH, occupied)
+        | ray_attacks(square, EAST, occupied)
+        | ray_attacks(square, SOUTH, occupied)
+        | ray_attacks(square, WEST, occupied)
+}
+
+/// The pieces of a position as one set per piece type and one per side, kept next to the
+/// square array so that lookups by square stay cheap.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub(crate) struct Bitboards {
+    pieces: [Bitboard; 6],
+    players: [Bitboard; 2],
+}
+
+impl Bitboards {
+    pub(crate) fn from_squares(board: &[Square; 64]) -> Self {
+        let mut bitboards = Bitboards {
+            pieces: [0; 6],
+            players: [0; 2],
+        };
+        for (square, value) in board.iter().enumerate() {
+            if let Some(chess_piece) = value.value {
+                bitboards.toggle(square, chess_piece);
+            }
+        }
+        bitboards
+    }
+
+    /// Adds the piece to `square` if it is not there, or removes it if it is.
+    pub(crate) fn toggle(&mut self, square: usize, chess_piece: ChessPiece) {
+        self.pieces[chess_piece.piece as usize] ^= bit(square);
+        self.players[chess_piece.player as usize] ^= bit(square);
+    }
+
+    pub(crate) fn pieces(&self, piece: Piece, player: Player) -> Bitboard {
+        self.pieces[piece as usize] & self.players[player as usize]
+    }
+
+    pub(crate) fn player(&self, player: Player) -> Bitboard {
+        self.players[player as usize]
+    }
+
+    pub(crate) fn occupied(&self) -> Bitboard {
+        self.players[0] | self.players[1]
+    }
+}
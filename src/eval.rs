@@ -0,0 +1,447 @@
+use std::fmt;
+use std::ops::{AddAssign, Sub};
+
+use crate::bitboard::{bishop_attacks, bit, rook_attacks, squares, Bitboard, KING_ATTACKS, KNIGHT_ATTACKS, PAWN_ATTACKS};
+use crate::{get_col, get_row, ChessBoard, Piece, Player};
+
+/// How much each piece adds to the game phase; all pieces but kings and pawns make up 24.
+const PHASE_WEIGHTS: [(Piece, i32); 4] = [(Piece::Knight, 1), (Piece::Bishop, 1), (Piece::Rook, 2), (Piece::Queen, 4)];
+const FULL_PHASE: i32 = 24;
+
+const FILE_A: Bitboard = 0x0101_0101_0101_0101;
+
+const DOUBLED_PAWN: Tapered = Tapered::new(-10, -20);
+const ISOLATED_PAWN: Tapered = Tapered::new(-10, -15);
+/// Bonus for a passed pawn by how many ranks it has advanced.
+const PASSED_PAWN: [Tapered; 8] = [
+    Tapered::new(0, 0),
+    Tapered::new(5, 10),
+    Tapered::new(10, 20),
+    Tapered::new(20, 40),
+    Tapered::new(35, 70),
+    Tapered::new(60, 120),
+    Tapered::new(100, 200),
+    Tapered::new(0, 0),
+];
+const PAWN_SHIELD: [i32; 2] = [10, 5];
+const SEMI_OPEN_KING_FILE: i32 = -15;
+const OPEN_KING_FILE: i32 = -10;
+/// Penalty per unit of attack on the squares around the king, in the middlegame only.
+const KING_ATTACK: i32 = -6;
+/// Bonus per square a piece can move to that is not attacked by an enemy pawn.
+const MOBILITY: [(Piece, Tapered); 4] = [
+    (Piece::Knight, Tapered::new(4, 4)),
+    (Piece::Bishop, Tapered::new(5, 5)),
+    (Piece::Rook, Tapered::new(2, 4)),
+    (Piece::Queen, Tapered::new(1, 2)),
+];
+
+// Piece-square tables from White's side, with rank 8 on the first row as in a diagram.
+#[rustfmt::skip]
+const PAWN_MIDDLEGAME: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    50,  50,  50,  50,  50,  50,  50,  50,
+    10,  10,  20,  30,  30,  20,  10,  10,
+     5,   5,  10,  25,  25,  10,   5,   5,
+     0,   0,   0,  20,  20,   0,   0,   0,
+     5,  -5, -10,   0,   0, -10,  -5,   5,
+     5,  10,  10, -20, -20,  10,  10,   5,
+     0,   0,   0,   0,   0,   0,   0,   0,
+];
+#[rustfmt::skip]
+const PAWN_ENDGAME: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    60,  60,  60,  60,  60,  60,  60,  60,
+    40,  40,  40,  40,  40,  40,  40,  40,
+    25,  25,  25,  25,  25,  25,  25,  25,
+    15,  15,  15,  15,  15,  15,  15,  15,
+     5,   5,   5,   5,   5,   5,   5,   5,
+     0,   0,   0,   0,   0,   0,   0,   0,
+     0,   0,   0,   0,   0,   0,   0,   0,
+];
+#[rustfmt::skip]
+const KNIGHT: [i32; 64] = [
+   -50, -40, -30, -30, -30, -30, -40, -50,
+   -40, -20,   0,   0,   0,   0, -20, -40,
+   -30,   0,  10,  15,  15,  10,   0, -30,
+   -30,   5,  15,  20,  20,  15,   5, -30,
+   -30,   0,  15,  20,  20,  15,   0, -30,
+   -30,   5,  10,  15,  15,  10,   5, -30,
+   -40, -20,   0,   5,   5,   0, -20, -40,
+   -50, -40, -30, -30, -30, -30, -40, -50,
+];
+#[rustfmt::skip]
+const BISHOP: [i32; 64] = [
+   -20, -10, -10, -10, -10, -10, -10, -20,
+   -10,   0,   0,   0,   0,   0,   0, -10,
+   -10,   0,   5,  10,  10,   5,   0, -10,
+   -10,   5,   5,  10,  10,   5,   5, -10,
+   -10,   0,  10,  10,  10,  10,   0, -10,
+   -10,  10,  10,  10,  10,  10,  10, -10,
+   -10,   5,   0,   0,   0,   0,   5, -10,
+   -20, -10, -10, -10, -10, -10, -10, -20,
+];
+#[rustfmt::skip]
+const ROOK: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+     5,  10,  10,  10,  10,  10,  10,   5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+     0,   0,   0,   5,   5,   0,   0,   0,
+];
+#[rustfmt::skip]
+const QUEEN: [i32; 64] = [
+   -20, -10, -10,  -5,  -5, -10, -10, -20,
+   -10,   0,   0,   0,   0,   0,   0, -10,
+   -10,   0,   5,   5,   5,   5,   0, -10,
+    -5,   0,   5,   5,   5,   5,   0,  -5,
+     0,   0,   5,   5,   5,   5,   0,  -5,
+   -10,   5,   5,   5,   5,   5,   0, -10,
+   -10,   0,   5,   0,   0,   0,   0, -10,
+   -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+#[rustfmt::skip]
+const KING_MIDDLEGAME: [i32; 64] = [
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -20, -30, -30, -40, -40, -30, -30, -20,
+   -10, -20, -20, -20, -20, -20, -20, -10,
+    20,  20,   0,   0,   0,   0,  20,  20,
+    20,  30,  10,   0,   0,  10,  30,  20,
+];
+#[rustfmt::skip]
+const KING_ENDGAME: [i32; 64] = [
+   -50, -40, -30, -20, -20, -30, -40, -50,
+   -30, -20, -10,   0,   0, -10, -20, -30,
+   -30, -10,  20,  30,  30,  20, -10, -30,
+   -30, -10,  30,  40,  40,  30, -10, -30,
+   -30, -10,  30,  40,  40,  30, -10, -30,
+   -30, -10,  20,  30,  30,  20, -10, -30,
+   -30, -30,   0,   0,   0,   0, -30, -30,
+   -50, -30, -30, -30, -30, -30, -30, -50,
+];
+
+/// A middlegame and an endgame value, blended according to the game phase.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub struct Tapered {
+    pub middlegame: i32,
+    pub endgame: i32,
+}
+
+impl Tapered {
+    pub const fn new(middlegame: i32, endgame: i32) -> Self {
+        Tapered { middlegame, endgame }
+    }
+
+    /// Blends the two values; `phase` runs from 24 in the opening down to 0 with only kings and
+    /// pawns left.
+    pub fn blend(self, phase: i32) -> i32 {
+        (self.middlegame * phase + self.endgame * (FULL_PHASE - phase)) / FULL_PHASE
+    }
+
+    fn scaled(self, factor: i32) -> Self {
+        Tapered::new(self.middlegame * factor, self.endgame * factor)
+    }
+}
+
+impl AddAssign for Tapered {
+    fn add_assign(&mut self, other: Tapered) {
+        self.middlegame += other.middlegame;
+        self.endgame += other.endgame;
+    }
+}
+
+impl Sub for Tapered {
+    type Output = Tapered;
+
+    fn sub(self, other: Tapered) -> Tapered {
+        Tapered::new(self.middlegame - other.middlegame, self.endgame - other.endgame)
+    }
+}
+
+/// The terms of the evaluation, each from White's point of view.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Evaluation {
+    pub material: Tapered,
+    pub piece_squares: Tapered,
+    pub pawn_structure: Tapered,
+    pub king_safety: Tapered,
+    pub mobility: Tapered,
+    pub phase: i32,
+    turn: Player,
+}
+
+impl Evaluation {
+    fn terms(&self) -> [(&'static str, Tapered); 5] {
+        [
+            ("material", self.material),
+            ("piece squares", self.piece_squares),
+            ("pawn structure", self.pawn_structure),
+            ("king safety", self.king_safety),
+            ("mobility", self.mobility),
+        ]
+    }
+
+    /// The blended sum of the terms from White's point of view.
+    pub fn white_score(&self) -> i32 {
+        let mut sum = Tapered::default();
+        for (_, term) in self.terms().iter() {
+            sum += *term;
+        }
+        sum.blend(self.phase)
+    }
+
+    /// The score from the point of view of the side to move, as used by the search.
+    pub fn score(&self) -> i32 {
+        match self.turn {
+            Player::White => self.white_score(),
+            Player::Black => -self.white_score(),
+        }
+    }
+}
+
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:<16}{:>12}{:>10}{:>10}", "term", "middlegame", "endgame", "blended")?;
+        for (name, term) in self.terms().iter() {
+            writeln!(f, "{:<16}{:>12}{:>10}{:>10}", name, term.middlegame, term.endgame, term.blend(self.phase))?;
+        }
+        writeln!(f, "phase {} of {}", self.phase, FULL_PHASE)?;
+        write!(f, "total {} for white, {} for the side to move", self.white_score(), self.score())
+    }
+}
+
+impl Piece {
+    /// The material value in centipawns; the king is priceless and counts as nothing.
+    pub fn value(self) -> i32 {
+        match self {
+            Piece::Pawn => 100,
+            Piece::Knight => 320,
+            Piece::Bishop => 330,
+            Piece::Rook => 500,
+            Piece::Queen => 900,
+            Piece::King => 0,
+        }
+    }
+}
+
+/// The score of the position in centipawns from the point of view of the side to move.
+pub fn evaluate(chess_board: &ChessBoard) -> i32 {
+    evaluation(chess_board).score()
+}
+
+/// The evaluation split into its terms, for finding out why a position scores as it does.
+pub fn evaluation(chess_board: &ChessBoard) -> Evaluation {
+    let bitboards = &chess_board.bitboards;
+    let mut phase = 0;
+    for &(piece, weight) in &PHASE_WEIGHTS {
+        for &player in &[Player::White, Player::Black] {
+            phase += weight * bitboards.pieces(piece, player).count_ones() as i32;
+        }
+    }
+
+    let terms = |player| {
+        [
+            material(chess_board, player),
+            piece_squares(chess_board, player),
+            pawn_structure(chess_board, player),
+            king_safety(chess_board, player),
+            mobility(chess_board, player),
+        ]
+    };
+    let white = terms(Player::White);
+    let black = terms(Player::Black);
+    Evaluation {
+        material: white[0] - black[0],
+        piece_squares: white[1] - black[1],
+        pawn_structure: white[2] - black[2],
+        king_safety: white[3] - black[3],
+        mobility: white[4] - black[4],
+        // Promotions can take the count past the opening's.
+        phase: phase.min(FULL_PHASE),
+        turn: chess_board.turn,
+    }
+}
+
+/// How many ranks `square` is from `player`'s back rank.
+fn relative_row(square: usize, player: Player) -> usize {
+    match player {
+        Player::White => get_row(square),
+        Player::Black => 7 - get_row(square),
+    }
+}
+
+/// The index into a piece-square table written from White's side with rank 8 first.
+fn table_index(square: usize, player: Player) -> usize {
+    (7 - relative_row(square, player)) * 8 + get_col(square)
+}
+
+fn file_mask(col: usize) -> Bitboard {
+    FILE_A << col
+}
+
+fn adjacent_files(col: usize) -> Bitboard {
+    let mut mask = 0;
+    if col > 0 {
+        mask |= file_mask(col - 1);
+    }
+    if col < 7 {
+        mask |= file_mask(col + 1);
+    }
+    mask
+}
+
+/// The rank `row` ranks up from `player`'s back rank.
+fn rank_mask(row: usize, player: Player) -> Bitboard {
+    match player {
+        Player::White => 0xff << (8 * row),
+        Player::Black => 0xff << (8 * (7 - row)),
+    }
+}
+
+/// The squares on the ranks in front of `square` as seen by `player`.
+fn ranks_ahead(square: usize, player: Player) -> Bitboard {
+    let row = get_row(square);
+    match player {
+        Player::White if row < 7 => !0 << (8 * (row + 1)),
+        Player::Black if row > 0 => !0 >> (8 * (8 - row)),
+        _ => 0,
+    }
+}
+
+fn material(chess_board: &ChessBoard, player: Player) -> Tapered {
+    let mut value = 0;
+    for &piece in &[Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
+        value += piece.value() * chess_board.bitboards.pieces(piece, player).count_ones() as i32;
+    }
+    Tapered::new(value, value)
+}
+
+fn piece_squares(chess_board: &ChessBoard, player: Player) -> Tapered {
+    let tables: [(Piece, &[i32; 64], &[i32; 64]); 6] = [
+        (Piece::Pawn, &PAWN_MIDDLEGAME, &PAWN_ENDGAME),
+        (Piece::Knight, &KNIGHT, &KNIGHT),
+        (Piece::Bishop, &BISHOP, &BISHOP),
+        (Piece::Rook, &ROOK, &ROOK),
+        (Piece::Queen, &QUEEN, &QUEEN),
+        (Piece::King, &KING_MIDDLEGAME, &KING_ENDGAME),
+    ];
+    let mut score = Tapered::default();
+    for &(piece, middlegame, endgame) in &tables {
+        for square in squares(chess_board.bitboards.pieces(piece, player)) {
+            let index = table_index(square, player);
+            score += Tapered::new(middlegame[index], endgame[index]);
+        }
+    }
+    score
+}
+
+/// Penalties for doubled and isolated pawns and bonuses for passed pawns.
+fn pawn_structure(chess_board: &ChessBoard, player: Player) -> Tapered {
+    let pawns = chess_board.bitboards.pieces(Piece::Pawn, player);
+    let enemy_pawns = chess_board.bitboards.pieces(Piece::Pawn, player.opponent());
+    let mut score = Tapered::default();
+    for col in 0..8 {
+        let on_file = (pawns & file_mask(col)).count_ones() as i32;
+        if on_file > 1 {
+            score += DOUBLED_PAWN.scaled(on_file - 1);
+        }
+        if on_file > 0 && pawns & adjacent_files(col) == 0 {
+            score += ISOLATED_PAWN.scaled(on_file);
+        }
+    }
+    for square in squares(pawns) {
+        let col = get_col(square);
+        let blockers = enemy_pawns & (file_mask(col) | adjacent_files(col)) & ranks_ahead(square, player);
+        // A pawn further up the same file keeps the one behind it from counting as passed.
+        let own_ahead = pawns & file_mask(col) & ranks_ahead(square, player);
+        if blockers == 0 && own_ahead == 0 {
+            score += PASSED_PAWN[relative_row(square, player)];
+        }
+    }
+    score
+}
+
+/// The pawn shield in front of the king, open files next to it and enemy pieces bearing on the
+/// squares around it. Only matters while there is material left to attack with.
+fn king_safety(chess_board: &ChessBoard, player: Player) -> Tapered {
+    let king = match chess_board.king_square(player) {
+        Some(square) => square,
+        None => return Tapered::default(),
+    };
+    let bitboards = &chess_board.bitboards;
+    let pawns = bitboards.pieces(Piece::Pawn, player);
+    let enemy_pawns = bitboards.pieces(Piece::Pawn, player.opponent());
+    let col = get_col(king);
+    let files = file_mask(col) | adjacent_files(col);
+    let mut score = 0;
+
+    let row = relative_row(king, player);
+    if row <= 1 {
+        for (distance, &bonus) in PAWN_SHIELD.iter().enumerate() {
+            let shield = pawns & files & rank_mask(row + 1 + distance, player);
+            score += bonus * shield.count_ones() as i32;
+        }
+    }
+
+    for file in (col.saturating_sub(1)..=(col + 1).min(7)).map(file_mask) {
+        if pawns & file == 0 {
+            score += SEMI_OPEN_KING_FILE;
+            if enemy_pawns & file == 0 {
+                score += OPEN_KING_FILE;
+            }
+        }
+    }
+
+    let zone = KING_ATTACKS[king] | bit(king);
+    let occupied = bitboards.occupied();
+    let enemy = player.opponent();
+    let mut units = 0;
+    for square in squares(bitboards.pieces(Piece::Knight, enemy)) {
+        units += 2 * (KNIGHT_ATTACKS[square] & zone).count_ones() as i32;
+    }
+    for square in squares(bitboards.pieces(Piece::Bishop, enemy)) {
+        units += 2 * (bishop_attacks(square, occupied) & zone).count_ones() as i32;
+    }
+    for square in squares(bitboards.pieces(Piece::Rook, enemy)) {
+        units += 3 * (rook_attacks(square, occupied) & zone).count_ones() as i32;
+    }
+    for square in squares(bitboards.pieces(Piece::Queen, enemy)) {
+        let attacks = bishop_attacks(square, occupied) | rook_attacks(square, occupied);
+        units += 5 * (attacks & zone).count_ones() as i32;
+    }
+    score += KING_ATTACK * units;
+
+    Tapered::new(score, 0)
+}
+
+/// Squares each piece can reach that are neither taken by an own piece nor covered by an enemy
+/// pawn.
+fn mobility(chess_board: &ChessBoard, player: Player) -> Tapered {
+    let bitboards = &chess_board.bitboards;
+    let occupied = bitboards.occupied();
+    let mut pawn_covered = 0;
+    for square in squares(bitboards.pieces(Piece::Pawn, player.opponent())) {
+        pawn_covered |= PAWN_ATTACKS[player.opponent() as usize][square];
+    }
+    let available = !bitboards.player(player) & !pawn_covered;
+
+    let mut score = Tapered::default();
+    for &(piece, weight) in &MOBILITY {
+        for square in squares(bitboards.pieces(piece, player)) {
+            let attacks = match piece {
+                Piece::Knight => KNIGHT_ATTACKS[square],
+                Piece::Bishop => bishop_attacks(square, occupied),
+                Piece::Rook => rook_attacks(square, occupied),
+                _ => bishop_attacks(square, occupied) | rook_attacks(square, occupied),
+            };
+            score += weight.scaled((attacks & available).count_ones() as i32);
+        }
+    }
+    score
+}
@@ -0,0 +1,351 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::bitboard::Bitboards;
+use crate::{get_col, get_row, parse_square, square_name, to_idx, CastlingRights, ChessBoard, ChessPiece, Piece, Player, Square};
+
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FenError {
+    /// FEN needs the four position fields, optionally followed by both move counters.
+    FieldCount(usize),
+    /// The piece placement must describe exactly eight ranks.
+    RankCount(usize),
+    /// A rank (numbered 1 to 8) does not add up to eight squares.
+    RankLength { rank: usize, squares: usize },
+    /// A run of empty squares must be a single digit from 1 to 8, e.g. not `0`, `9` or `44`.
+    InvalidEmptyCount { rank: usize, count: String },
+    InvalidPiece(char),
+    KingCount { player: Player, count: usize },
+    PawnOnBackRank(usize),
+    InvalidSideToMove(String),
+    InvalidCastling(String),
+    InvalidEnPassant(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+    /// The side that just moved has left its own king in check.
+    OpponentInCheck(Player),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::FieldCount(count) => write!(f, "expected 4 or 6 space separated fields, found {}", count),
+            FenError::RankCount(count) => write!(f, "expected 8 ranks separated by '/', found {}", count),
+            FenError::RankLength { rank, squares } => {
+                write!(f, "rank {} describes {} squares instead of 8", rank, squares)
+            }
+            FenError::InvalidEmptyCount { rank, count } => {
+                write!(f, "rank {} counts '{}' empty squares, expected a single digit from 1 to 8", rank, count)
+            }
+            FenError::InvalidPiece(letter) => write!(f, "'{}' is not a piece letter", letter),
+            FenError::KingCount { player, count } => write!(f, "{} must have exactly one king, found {}", player, count),
+            FenError::PawnOnBackRank(square) => write!(f, "pawn on {} cannot stand on the first or last rank", square_name(*square)),
+            FenError::InvalidSideToMove(field) => write!(f, "side to move must be 'w' or 'b', found '{}'", field),
+            FenError::InvalidCastling(field) => write!(f, "castling rights must be '-' or a subset of 'KQkq', found '{}'", field),
+            FenError::InvalidEnPassant(field) => {
+                write!(f, "en passant square must be '-' or the square a pawn just passed over, found '{}'", field)
+            }
+            FenError::InvalidHalfmoveClock(field) => write!(f, "halfmove clock must be a number, found '{}'", field),
+            FenError::InvalidFullmoveNumber(field) => write!(f, "fullmove number must be a positive number, found '{}'", field),
+            FenError::OpponentInCheck(player) => write!(f, "{} is in check but it is not their turn to move", player),
+        }
+    }
+}
+
+impl Error for FenError {}
+
+impl ChessBoard {
+    /// Sets up a position from Forsyth-Edwards Notation. The two move counters may be omitted.
+    pub fn from_fen(fen: &str) -> Result<ChessBoard, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 4 && fields.len() != 6 {
+            return Err(FenError::FieldCount(fields.len()));
+        }
+
+        let board = parse_placement(fields[0])?;
+
+        let turn = match fields[1] {
+            "w" => Player::White,
+            "b" => Player::Black,
+            other => return Err(FenError::InvalidSideToMove(other.to_string())),
+        };
+
+        let castling = parse_castling(fields[2])?;
+
+        // The square must lie behind a pawn of the side not to move that just advanced two squares.
+        let (en_passant_row, pawn_row) = match turn {
+            Player::White => (5, 4),
+            Player::Black => (2, 3),
+        };
+        let en_passant = match fields[3] {
+            "-" => None,
+            field => match parse_square(field) {
+                Some(square)
+                    if field == field.to_lowercase()
+                        && get_row(square) == en_passant_row
+                        && board[to_idx(pawn_row, get_col(square))].value
+                            == Some(ChessPiece {
+                                piece: Piece::Pawn,
+                                player: turn.opponent(),
+                            }) =>
+                {
+                    Some(square)
+                }
+                _ => return Err(FenError::InvalidEnPassant(field.to_string())),
+            },
+        };
+
+        let (halfmove_clock, fullmove_number) = if fields.len() == 6 {
+            let halfmove_clock = fields[4]
+                .parse::<u32>()
+                .map_err(|_| FenError::InvalidHalfmoveClock(fields[4].to_string()))?;
+            let fullmove_number = match fields[5].parse::<u32>() {
+                Ok(number) if number > 0 => number,
+                _ => return Err(FenError::InvalidFullmoveNumber(fields[5].to_string())),
+            };
+            (halfmove_clock, fullmove_number)
+        } else {
+            (0, 1)
+        };
+
+        let mut chess_board = ChessBoard {
+            board,
+            bitboards: Bitboards::from_squares(&board),
+            turn,
+            castling,
+            en_passant,
+            halfmove_clock,
+            fullmove_number,
+            hash: 0,
+            history: Vec::new(),
+        };
+        if chess_board.in_check(turn.opponent()) {
+            return Err(FenError::OpponentInCheck(turn.opponent()));
+        }
+        chess_board.hash = chess_board.compute_zobrist_key();
+        Ok(chess_board)
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut ranks = Vec::new();
+        for row in (0..8).rev() {
+            let mut rank = String::new();
+            let mut empty = 0;
+            for col in 0..8 {
+                match self.board[to_idx(row, col)].value {
+                    Some(chess_piece) => {
+                        if empty > 0 {
+                            rank.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        rank.push(chess_piece.letter());
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                rank.push_str(&empty.to_string());
+            }
+            ranks.push(rank);
+        }
+
+        let turn = match self.turn {
+            Player::White => "w",
+            Player::Black => "b",
+        };
+
+        let mut castling = String::new();
+        for &(allowed, letter) in &[
+            (self.castling.white_kingside, 'K'),
+            (self.castling.white_queenside, 'Q'),
+            (self.castling.black_kingside, 'k'),
+            (self.castling.black_queenside, 'q'),
+        ] {
+            if allowed {
+                castling.push(letter);
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let en_passant = match self.en_passant {
+            Some(square) => square_name(square),
+            None => "-".to_string(),
+        };
+
+        format!(
+            "{} {} {} {} {} {}",
+            ranks.join("/"),
+            turn,
+            castling,
+            en_passant,
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+}
+
+fn parse_placement(field: &str) -> Result<[Square; 64], FenError> {
+    let ranks: Vec<&str> = field.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(FenError::RankCount(ranks.len()));
+    }
+
+    let mut board = [Square::new(); 64];
+    let mut kings = [0, 0];
+    for (rank_idx, rank) in ranks.iter().enumerate() {
+        let row = 7 - rank_idx;
+        let mut col = 0;
+        let mut rest = *rank;
+        while let Some(letter) = rest.chars().next() {
+            if letter.is_ascii_digit() {
+                let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+                let count = &rest[..digits];
+                if digits > 1 || !('1'..='8').contains(&letter) {
+                    return Err(FenError::InvalidEmptyCount {
+                        rank: row + 1,
+                        count: count.to_string(),
+                    });
+                }
+                col += letter as usize - '0' as usize;
+                rest = &rest[digits..];
+                continue;
+            }
+            rest = &rest[letter.len_utf8()..];
+            let piece = Piece::from_letter(letter.to_ascii_uppercase()).ok_or(FenError::InvalidPiece(letter))?;
+            let player = if letter.is_ascii_uppercase() { Player::White } else { Player::Black };
+            if col < 8 {
+                let square = to_idx(row, col);
+                if piece == Piece::Pawn && (row == 0 || row == 7) {
+                    return Err(FenError::PawnOnBackRank(square));
+                }
+                if piece == Piece::King {
+                    kings[player as usize] += 1;
+                }
+                board[square].init(ChessPiece { piece, player });
+            }
+            col += 1;
+        }
+        if col != 8 {
+            return Err(FenError::RankLength { rank: row + 1, squares: col });
+        }
+    }
+
+    for &player in &[Player::White, Player::Black] {
+        if kings[player as usize] != 1 {
+            return Err(FenError::KingCount {
+                player,
+                count: kings[player as usize],
+            });
+        }
+    }
+    Ok(board)
+}
+
+fn parse_castling(field: &str) -> Result<CastlingRights, FenError> {
+    let mut castling = CastlingRights::none();
+    if field == "-" {
+        return Ok(castling);
+    }
+    for letter in field.chars() {
+        let right = match letter {
+            'K' => &mut castling.white_kingside,
+            'Q' => &mut castling.white_queenside,
+            'k' => &mut castling.black_kingside,
+            'q' => &mut castling.black_queenside,
+            _ => return Err(FenError::InvalidCastling(field.to_string())),
+        };
+        if *right {
+            return Err(FenError::InvalidCastling(field.to_string()));
+        }
+        *right = true;
+    }
+    Ok(castling)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(fen: &str) -> FenError {
+        ChessBoard::from_fen(fen).err().unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        for fen in &[
+            STARTING_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ] {
+            assert_eq!(ChessBoard::from_fen(fen).unwrap().to_fen(), *fen);
+        }
+    }
+
+    #[test]
+    fn move_counters_are_optional() {
+        let chess_board = ChessBoard::from_fen("4k3/8/8/8/8/8/8/4K3 b - -").unwrap();
+        assert_eq!(chess_board.to_fen(), "4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    }
+
+    #[test]
+    fn rejects_malformed_placement() {
+        assert_eq!(error("4k3/8/8/8/8/8/4K3 w - - 0 1"), FenError::RankCount(7));
+        assert_eq!(error("4k3/8/8/8/8/8/8/4K2 w - - 0 1"), FenError::RankLength { rank: 1, squares: 7 });
+        assert_eq!(error("4k3/8/8/8/8/8/8/4K3x w - - 0 1"), FenError::InvalidPiece('x'));
+        assert_eq!(
+            error("4k3/8/8/08/8/8/8/4K3 w - - 0 1"),
+            FenError::InvalidEmptyCount {
+                rank: 5,
+                count: "08".to_string()
+            }
+        );
+        assert_eq!(
+            error("4k3/8/8/44/8/8/8/4K3 w - - 0 1"),
+            FenError::InvalidEmptyCount {
+                rank: 5,
+                count: "44".to_string()
+            }
+        );
+        assert_eq!(
+            error("4k3/8/8/9/8/8/8/4K3 w - - 0 1"),
+            FenError::InvalidEmptyCount {
+                rank: 5,
+                count: "9".to_string()
+            }
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/8 w - - 0 1"),
+            FenError::KingCount {
+                player: Player::White,
+                count: 0
+            }
+        );
+        assert_eq!(error("P3k3/8/8/8/8/8/8/4K3 w - - 0 1"), FenError::PawnOnBackRank(56));
+    }
+
+    #[test]
+    fn rejects_malformed_fields() {
+        assert_eq!(error("4k3/8/8/8/8/8/8/4K3 w - - 0"), FenError::FieldCount(5));
+        assert_eq!(error("4k3/8/8/8/8/8/8/4K3 x - - 0 1"), FenError::InvalidSideToMove("x".to_string()));
+        assert_eq!(error("4k3/8/8/8/8/8/8/4K3 w KK - 0 1"), FenError::InvalidCastling("KK".to_string()));
+        assert_eq!(error("4k3/8/8/8/8/8/8/4K3 w - - x 1"), FenError::InvalidHalfmoveClock("x".to_string()));
+        assert_eq!(error("4k3/8/8/8/8/8/8/4K3 w - - 0 0"), FenError::InvalidFullmoveNumber("0".to_string()));
+    }
+
+    #[test]
+    fn rejects_en_passant_without_a_pawn_to_take() {
+        assert_eq!(error("4k3/8/8/8/8/8/3PP3/4K3 w - e3 0 1"), FenError::InvalidEnPassant("e3".to_string()));
+        assert_eq!(error("4k3/8/8/4p3/8/8/8/4K3 b - e6 0 1"), FenError::InvalidEnPassant("e6".to_string()));
+        assert!(ChessBoard::from_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").is_ok());
+    }
+
+    #[test]
+    fn rejects_side_not_to_move_in_check() {
+        assert_eq!(error("4k3/4R3/8/8/8/8/8/4K3 w - - 0 1"), FenError::OpponentInCheck(Player::Black));
+        assert!(ChessBoard::from_fen("4k3/4R3/8/8/8/8/8/4K3 b - - 0 1").is_ok());
+    }
+}
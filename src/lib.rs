@@ -0,0 +1,813 @@
+#![allow(clippy::needless_return)]
+
+use std::error::Error;
+use std::fmt;
+
+mod bitboard;
+mod eval;
+mod fen;
+mod make;
+mod movegen;
+mod ordering;
+mod perft;
+mod pgn;
+mod render;
+mod san;
+mod script;
+mod search;
+mod status;
+mod svg;
+mod transposition;
+mod uci;
+mod zobrist;
+
+pub use eval::{evaluate, evaluation, Evaluation, Tapered};
+pub use fen::{FenError, STARTING_FEN};
+pub use make::Undo;
+pub use movegen::ChessMove;
+pub use perft::{PerftPosition, PERFT_SUITE};
+pub use pgn::{read_pgn, PgnError, PgnGame, PgnMove};
+pub use render::{BoardView, Glyphs};
+pub use san::SanError;
+pub use script::{run_script, ScriptError};
+pub use search::{SearchLimits, SearchResult, Searcher, MATE};
+pub use status::{DrawReason, GameStatus};
+pub use svg::SvgDiagram;
+pub use transposition::{TranspositionTable, DEFAULT_HASH_MB, MAX_HASH_MB};
+
+use bitboard::Bitboards;
+pub use uci::UciError;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Piece {
+    King,
+    Queen,
+    Knight,
+    Bishop,
+    Rook,
+    Pawn
+}
+
+impl Piece {
+    pub const PROMOTIONS: [Piece; 4] = [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight];
+
+    /// The piece for an uppercase letter as used in algebraic notation, e.g. `N` for a knight.
+    pub fn from_letter(letter: char) -> Option<Piece> {
+        match letter {
+            'K' => Some(Piece::King),
+            'Q' => Some(Piece::Queen),
+            'N' => Some(Piece::Knight),
+            'B' => Some(Piece::Bishop),
+            'R' => Some(Piece::Rook),
+            'P' => Some(Piece::Pawn),
+            _ => None,
+        }
+    }
+
+    pub fn letter(self) -> char {
+        match self {
+            Piece::King => 'K',
+            Piece::Queen => 'Q',
+            Piece::Knight => 'N',
+            Piece::Bishop => 'B',
+            Piece::Rook => 'R',
+            Piece::Pawn => 'P',
+        }
+    }
+}
+
+impl fmt::Display for Piece {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Piece::King => "king",
+            Piece::Queen => "queen",
+            Piece::Knight => "knight",
+            Piece::Bishop => "bishop",
+            Piece::Rook => "rook",
+            Piece::Pawn => "pawn",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Player {
+    Black,
+    White
+}
+
+impl Player {
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::White => Player::Black,
+            Player::Black => Player::White,
+        }
+    }
+}
+
+impl fmt::Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Player::White => write!(f, "white"),
+            Player::Black => write!(f, "black"),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct ChessPiece {
+    piece: Piece,
+    player: Player 
+}
+
+impl ChessPiece {
+    pub fn piece(&self) -> Piece {
+        self.piece
+    }
+
+    pub fn player(&self) -> Player {
+        self.player
+    }
+
+    /// The letter FEN uses for the piece: uppercase for white, lowercase for black.
+    pub fn letter(&self) -> char {
+        match self.player {
+            Player::White => self.piece.letter(),
+            Player::Black => self.piece.letter().to_ascii_lowercase(),
+        }
+    }
+}
+
+impl fmt::Display for ChessPiece {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.player, self.piece)
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Square {
+    value: Option<ChessPiece>,
+}
+
+impl Square {
+    fn new() -> Self {
+        Square {
+            value: None
+        }
+    }
+
+    pub fn init(&mut self, piece: ChessPiece){
+        self.value = Some(piece);
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.value {
+            Some(chess_piece) => write!(f, "{}", chess_piece.letter()),
+            None => write!(f, "."),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub struct CastlingRights {
+    pub white_kingside: bool,
+    pub white_queenside: bool,
+    pub black_kingside: bool,
+    pub black_queenside: bool,
+}
+
+impl CastlingRights {
+    pub fn all() -> Self {
+        CastlingRights {
+            white_kingside: true,
+            white_queenside: true,
+            black_kingside: true,
+            black_queenside: true,
+        }
+    }
+
+    pub fn none() -> Self {
+        CastlingRights {
+            white_kingside: false,
+            white_queenside: false,
+            black_kingside: false,
+            black_queenside: false,
+        }
+    }
+
+    pub fn kingside(&self, player: Player) -> bool {
+        match player {
+            Player::White => self.white_kingside,
+            Player::Black => self.black_kingside,
+        }
+    }
+
+    pub fn queenside(&self, player: Player) -> bool {
+        match player {
+            Player::White => self.white_queenside,
+            Player::Black => self.black_queenside,
+        }
+    }
+
+    pub fn revoke(&mut self, player: Player) {
+        match player {
+            Player::White => {
+                self.white_kingside = false;
+                self.white_queenside = false;
+            }
+            Player::Black => {
+                self.black_kingside = false;
+                self.black_queenside = false;
+            }
+        }
+    }
+
+    /// Drops the right tied to a rook's home square once anything moves from or to it.
+    pub fn revoke_square(&mut self, square: usize) {
+        match square {
+            0 => self.white_queenside = false,
+            7 => self.white_kingside = false,
+            56 => self.black_queenside = false,
+            63 => self.black_kingside = false,
+            _ => (),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ChessBoard {
+    board: [Square; 64],
+    bitboards: Bitboards,
+    turn: Player,
+    castling: CastlingRights,
+    en_passant: Option<usize>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    /// The Zobrist key of the position, see `zobrist_key`.
+    hash: u64,
+    /// The Zobrist keys of the positions before each move made on this board.
+    history: Vec<u64>,
+}
+
+impl ChessBoard {
+    pub fn new() -> Self {
+        let mut chess_board = [Square::new(); 64];
+        chess_board[0].init(ChessPiece {
+            piece: Piece::Rook,
+            player: Player::White
+        });
+        chess_board[7].init(ChessPiece {
+            piece: Piece::Rook,
+            player: Player::White
+        });
+
+        chess_board[1].init(ChessPiece {
+            piece: Piece::Knight,
+            player: Player::White
+        });
+        chess_board[6].init(ChessPiece {
+            piece: Piece::Knight,
+            player: Player::White
+        });
+
+        chess_board[2].init(ChessPiece {
+            piece: Piece::Bishop,
+            player: Player::White
+        });
+        chess_board[5].init(ChessPiece {
+            piece: Piece::Bishop,
+            player: Player::White
+        });
+
+        chess_board[3].init(ChessPiece {
+            piece: Piece::Queen,
+            player: Player::White
+        });
+        chess_board[4].init(ChessPiece {
+            piece: Piece::King,
+            player: Player::White
+        });
+
+
+        chess_board[56].init(ChessPiece {
+            piece: Piece::Rook,
+            player: Player::Black
+        });
+        chess_board[63].init(ChessPiece {
+            piece: Piece::Rook,
+            player: Player::Black
+        });
+
+        chess_board[57].init(ChessPiece {
+            piece: Piece::Knight,
+            player: Player::Black
+        });
+        chess_board[62].init(ChessPiece {
+            piece: Piece::Knight,
+            player: Player::Black
+        });
+
+        chess_board[58].init(ChessPiece {
+            piece: Piece::Bishop,
+            player: Player::Black
+        });
+        chess_board[61].init(ChessPiece {
+            piece: Piece::Bishop,
+            player: Player::Black
+        });
+
+        chess_board[59].init(ChessPiece {
+            piece: Piece::Queen,
+            player: Player::Black
+        });
+        chess_board[60].init(ChessPiece {
+            piece: Piece::King,
+            player: Player::Black
+        });
+
+        for square in &mut chess_board[8..16] {
+            square.init(ChessPiece {
+                piece: Piece::Pawn,
+                player: Player::White
+            })
+        }
+
+        for square in &mut chess_board[48..56] {
+            square.init(ChessPiece {
+                piece: Piece::Pawn,
+                player: Player::Black
+            });
+        }
+
+        let mut chess_board = ChessBoard {
+            board: chess_board,
+            bitboards: Bitboards::from_squares(&chess_board),
+            turn: Player::White,
+            castling: CastlingRights::all(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            hash: 0,
+            history: Vec::new(),
+        };
+        chess_board.hash = chess_board.compute_zobrist_key();
+        chess_board
+    }
+
+    pub fn piece_at(&self, square: usize) -> Option<ChessPiece> {
+        self.board.get(square).and_then(|square| square.value)
+    }
+
+    pub fn turn(&self) -> Player {
+        self.turn
+    }
+
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling
+    }
+
+    /// The square a pawn skipped with a double push on the previous move, if any.
+    pub fn en_passant(&self) -> Option<usize> {
+        self.en_passant
+    }
+
+    /// Moves since the last capture or pawn move.
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    /// Starts at 1 and increments after each Black move.
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+}
+
+impl Default for ChessBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug)]
+pub struct Operation {
+    pub op: String,
+    pub from: usize,
+    pub to: usize,
+    pub promotion: Option<Piece>,
+}
+
+impl From<ChessMove> for Operation {
+    fn from(chess_move: ChessMove) -> Self {
+        Operation {
+            op: "Move".to_string(),
+            from: chess_move.from,
+            to: chess_move.to,
+            promotion: chess_move.promotion,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The command was blank.
+    Empty,
+    /// The command word is not one that is understood, e.g. `Jump E2,E4`.
+    UnknownOp(String),
+    /// The command has no move after the command word.
+    MissingMove,
+    /// A coordinate move has a source square but no destination, e.g. `Move E2`.
+    MissingDestination,
+    /// Something follows the destination square, e.g. `Move E2,E4,E5`.
+    TrailingInput(String),
+    BadSquare(String),
+    BadPromotion(String),
+    San(SanError),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "empty command"),
+            ParseError::UnknownOp(op) => write!(f, "unknown command '{}'", op),
+            ParseError::MissingMove => write!(f, "missing move, e.g. Move E2,E4"),
+            ParseError::MissingDestination => write!(f, "missing destination square, e.g. Move E2,E4"),
+            ParseError::TrailingInput(rest) => write!(f, "unexpected '{}' after the move", rest),
+            ParseError::BadSquare(square) => write!(f, "'{}' is not a square between A1 and H8", square),
+            ParseError::BadPromotion(piece) => write!(f, "cannot promote to '{}', expected one of Q, R, B or N", piece),
+            ParseError::San(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<SanError> for ParseError {
+    fn from(error: SanError) -> Self {
+        ParseError::San(error)
+    }
+}
+
+pub fn parse(content: &str) -> Result<Operation, ParseError> {
+    let mut moves = content.split_whitespace();
+    let op = match moves.next() {
+        Some(valid_op) if valid_op.eq_ignore_ascii_case("Move") => valid_op.to_string(),
+        Some(unknown_op) => return Err(ParseError::UnknownOp(unknown_op.to_string())),
+        None => return Err(ParseError::Empty),
+    };
+    let mut directions = match moves.next() {
+        Some(valid_direction) => valid_direction.split(','),
+        None => return Err(ParseError::MissingMove),
+    };
+    let start = match directions.next() {
+        Some(start) => start,
+        None => return Err(ParseError::MissingMove),
+    };
+    let dest = match directions.next() {
+        Some(dest) if !dest.is_empty() => dest,
+        _ => return Err(ParseError::MissingDestination),
+    };
+    let rest: String = directions
+        .map(|square| format!(",{}", square))
+        .chain(moves.map(|word| format!(" {}", word)))
+        .collect();
+    if !rest.is_empty() {
+        return Err(ParseError::TrailingInput(rest.trim_start().to_string()));
+    }
+    let (dest, promotion) = match dest.split_once('=') {
+        Some((square, piece)) => (square, Some(parse_promotion(piece)?)),
+        None => (dest, None),
+    };
+    let from_idx = convert_to_idx(start)?;
+    let to_idx = convert_to_idx(dest)?;
+    return Ok(Operation {
+        op,
+        from: from_idx,
+        to: to_idx,
+        promotion,
+    });
+}
+
+/// Parses a move command in coordinate form (`Move E2,E4`), UCI notation (`Move e7e8q`) or
+/// Standard Algebraic Notation (`Move Nf3` or just `Nf3`). SAN is resolved against `chess_board`,
+/// the position the move will be played in, so that pieces like `Rad1` are disambiguated correctly.
+pub fn parse_command(content: &str, chess_board: &ChessBoard) -> Result<Operation, ParseError> {
+    let words: Vec<&str> = content.split_whitespace().collect();
+    let text = match words.as_slice() {
+        [] => return Err(ParseError::Empty),
+        [_, coordinates, ..] if coordinates.contains(',') => return parse(content),
+        [op] if op.eq_ignore_ascii_case("Move") => return Err(ParseError::MissingMove),
+        [text] => text,
+        [op, square]
+            if op.eq_ignore_ascii_case("Move")
+                && parse_square(square).is_some()
+                && chess_board.find_san(square).is_err() =>
+        {
+            return Err(ParseError::MissingDestination)
+        }
+        [op, text] if op.eq_ignore_ascii_case("Move") => text,
+        [op, ..] if !op.eq_ignore_ascii_case("Move") => return Err(ParseError::UnknownOp(op.to_string())),
+        _ => return Err(SanError::Invalid(content.trim().to_string()).into()),
+    };
+    let is_playable = |chess_move: ChessMove| {
+        chess_board
+            .legal_moves()
+            .iter()
+            .any(|legal| legal.from == chess_move.from && legal.to == chess_move.to)
+    };
+    let chess_move = match ChessMove::from_uci(text) {
+        Ok(chess_move) if is_playable(chess_move) => chess_move,
+        // Moves such as `B1e2` read as UCI too, so SAN gets a try before the UCI move is rejected.
+        Ok(chess_move) => chess_board.find_san(text).unwrap_or(chess_move),
+        Err(_) => chess_board.find_san(text)?,
+    };
+    Ok(Operation::from(chess_move))
+}
+
+fn parse_promotion(piece: &str) -> Result<Piece, ParseError> {
+    let mut letters = piece.chars().map(|letter| letter.to_ascii_uppercase());
+    return match (letters.next().and_then(Piece::from_letter), letters.next()) {
+        (Some(promotion), None) if Piece::PROMOTIONS.contains(&promotion) => Ok(promotion),
+        (_, _) => Err(ParseError::BadPromotion(piece.to_string())),
+    };
+}
+
+/// The square index of a coordinate such as `E4`. Lowercase files are accepted too.
+pub fn convert_to_idx(chess_move: &str) -> Result<usize, ParseError> {
+    return parse_square(chess_move).ok_or_else(|| ParseError::BadSquare(chess_move.to_string()));
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MoveError {
+    /// There is no piece on the source square.
+    EmptySquare(usize),
+    /// The piece on the source square belongs to the player not on move.
+    WrongSideToMove,
+    /// The piece does not move that way, or its path is blocked.
+    Illegal,
+    /// The move would leave the mover's own king in check.
+    LeavesKingInCheck,
+    /// A pawn reaching the last rank was not given a piece to promote to.
+    MissingPromotion,
+    /// A promotion piece was given for a move that does not promote.
+    UnexpectedPromotion,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveError::EmptySquare(square) => write!(f, "there is no piece on {}", square_name(*square)),
+            MoveError::WrongSideToMove => write!(f, "it is not that side's turn to move"),
+            MoveError::Illegal => write!(f, "that piece cannot move there"),
+            MoveError::LeavesKingInCheck => write!(f, "that move would leave the king in check"),
+            MoveError::MissingPromotion => write!(f, "promotion needs a piece, e.g. E8=Q"),
+            MoveError::UnexpectedPromotion => write!(f, "only a pawn reaching the last rank can promote"),
+        }
+    }
+}
+
+impl Error for MoveError {}
+
+/// Finds the legal move described by `op`, explaining why there is none.
+pub fn resolve_move(op: &Operation, chess_board: &ChessBoard) -> Result<ChessMove, MoveError> {
+    match chess_board.piece_at(op.from) {
+        None => return Err(MoveError::EmptySquare(op.from)),
+        Some(chess_piece) if chess_piece.player != chess_board.turn => return Err(MoveError::WrongSideToMove),
+        Some(_) => (),
+    }
+    let candidates: Vec<ChessMove> = chess_board
+        .legal_moves()
+        .into_iter()
+        .filter(|chess_move| chess_move.from == op.from && chess_move.to == op.to)
+        .collect();
+    if candidates.is_empty() {
+        let is_reachable = chess_board
+            .pseudo_legal_moves()
+            .iter()
+            .any(|chess_move| chess_move.from == op.from && chess_move.to == op.to);
+        return Err(if is_reachable { MoveError::LeavesKingInCheck } else { MoveError::Illegal });
+    }
+    let is_promotion = candidates.iter().any(|chess_move| chess_move.promotion.is_some());
+    match (op.promotion, is_promotion) {
+        (None, true) => Err(MoveError::MissingPromotion),
+        (Some(_), false) => Err(MoveError::UnexpectedPromotion),
+        (promotion, _) => Ok(ChessMove {
+            from: op.from,
+            to: op.to,
+            promotion,
+        }),
+    }
+}
+
+pub fn validate_move(op: &Operation, chess_board: &ChessBoard) -> bool {
+    resolve_move(op, chess_board).is_ok()
+}
+
+/// Plays `op` for the side to move, leaving the board untouched if it is not legal. The returned
+/// record takes the move back with `unmake_move`.
+pub fn execute_move(op: &Operation, chess_board: &mut ChessBoard) -> Result<Undo, MoveError> {
+    let chess_move = resolve_move(op, chess_board)?;
+    Ok(chess_board.make_move(chess_move))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CommandError {
+    Parse(ParseError),
+    Move(MoveError),
+    /// No more moves can be played once the game has ended.
+    GameOver(GameStatus),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CommandError::Parse(error) => write!(f, "{}", error),
+            CommandError::Move(error) => write!(f, "{}", error),
+            CommandError::GameOver(status) => write!(f, "the game is already over, {}", status),
+        }
+    }
+}
+
+impl Error for CommandError {}
+
+impl From<ParseError> for CommandError {
+    fn from(error: ParseError) -> Self {
+        CommandError::Parse(error)
+    }
+}
+
+impl From<MoveError> for CommandError {
+    fn from(error: MoveError) -> Self {
+        CommandError::Move(error)
+    }
+}
+
+/// Parses a move command with `parse_command` and plays it, unless the game has already ended.
+pub fn play_command(content: &str, chess_board: &mut ChessBoard) -> Result<Undo, CommandError> {
+    let status = chess_board.status();
+    if status.is_over() {
+        return Err(CommandError::GameOver(status));
+    }
+    let op = parse_command(content, chess_board)?;
+    Ok(execute_move(&op, chess_board)?)
+}
+
+/// Whether playing `op` puts the opponent's king in check.
+pub fn is_checked(op: &Operation, chess_board: &ChessBoard) -> bool {
+    return match resolve_move(op, chess_board) {
+        Ok(chess_move) => {
+            let mut next = chess_board.clone();
+            next.make_move(chess_move);
+            next.in_check(next.turn)
+        },
+        Err(_) => false,
+    };
+}
+
+pub fn is_empty_square(chess_board: &ChessBoard, row: usize, col: usize) -> bool {
+    let square_idx = to_idx(row, col);
+    let board = chess_board.board;
+    if square_idx < 64 {
+        return board[square_idx].value.is_none();
+    }
+    return false;
+}
+
+/// Whether playing `op` checkmates the opponent.
+pub fn is_checkmated(op: &Operation, chess_board: &ChessBoard) -> bool {
+    return match resolve_move(op, chess_board) {
+        Ok(chess_move) => {
+            let mut next = chess_board.clone();
+            next.make_move(chess_move);
+            matches!(next.status(), GameStatus::Checkmate { .. })
+        },
+        Err(_) => false,
+    };
+}
+
+pub fn get_col(idx: usize) -> usize {
+    return idx % 8;
+}
+
+pub fn get_row(idx: usize) -> usize {
+    return idx / 8;
+}
+
+pub fn to_idx(row: usize, col: usize) -> usize {
+    return row * 8 + col;
+}
+
+/// The lowercase coordinate of a square index, e.g. `e4` for 28.
+pub fn square_name(idx: usize) -> String {
+    let file = (b'a' + get_col(idx) as u8) as char;
+    let rank = (b'1' + get_row(idx) as u8) as char;
+    format!("{}{}", file, rank)
+}
+
+/// The square index of a coordinate such as `e4` or `E4`.
+pub fn parse_square(name: &str) -> Option<usize> {
+    let mut chars = name.chars();
+    let col = match chars.next()?.to_ascii_lowercase() {
+        file @ 'a'..='h' => file as usize - 'a' as usize,
+        _ => return None,
+    };
+    let row = match chars.next()? {
+        rank @ '1'..='8' => rank as usize - '1' as usize,
+        _ => return None,
+    };
+    match chars.next() {
+        Some(_) => None,
+        None => Some(to_idx(row, col)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(fen: &str) -> ChessBoard {
+        ChessBoard::from_fen(fen).unwrap()
+    }
+
+    #[test]
+    fn san_that_also_reads_as_uci() {
+        let chess_board = board("4k3/8/8/8/8/5B2/8/4KB2 w - - 0 1");
+        let op = parse_command("B1e2", &chess_board).unwrap();
+        assert_eq!((op.from, op.to), (5, 12));
+        let op = parse_command("Move B1e2", &chess_board).unwrap();
+        assert_eq!((op.from, op.to), (5, 12));
+        let op = parse_command("f3e2", &chess_board).unwrap();
+        assert_eq!((op.from, op.to), (21, 12));
+    }
+
+    #[test]
+    fn parses_coordinate_moves() {
+        let op = parse("Move E2,E4").unwrap();
+        assert_eq!((op.from, op.to, op.promotion), (12, 28, None));
+        let op = parse("move e7,e8=n").unwrap();
+        assert_eq!((op.from, op.to, op.promotion), (52, 60, Some(Piece::Knight)));
+    }
+
+    #[test]
+    fn rejects_malformed_coordinate_moves() {
+        assert_eq!(parse("").err(), Some(ParseError::Empty));
+        assert_eq!(parse("Jump E2,E4").err(), Some(ParseError::UnknownOp("Jump".to_string())));
+        assert_eq!(parse("Move").err(), Some(ParseError::MissingMove));
+        assert_eq!(parse("Move E2").err(), Some(ParseError::MissingDestination));
+        assert_eq!(parse("Move E2,").err(), Some(ParseError::MissingDestination));
+        assert_eq!(parse("Move E2,E4,E5").err(), Some(ParseError::TrailingInput(",E5".to_string())));
+        assert_eq!(parse("Move E2,E4 garbage").err(), Some(ParseError::TrailingInput("garbage".to_string())));
+        assert_eq!(parse("Move E2,E9").err(), Some(ParseError::BadSquare("E9".to_string())));
+        assert_eq!(parse("Move Z2,E4").err(), Some(ParseError::BadSquare("Z2".to_string())));
+        assert_eq!(parse("Move E2,E4é").err(), Some(ParseError::BadSquare("E4é".to_string())));
+        assert_eq!(parse("Move E7,E8=K").err(), Some(ParseError::BadPromotion("K".to_string())));
+        assert_eq!(parse("Move E7,E8=").err(), Some(ParseError::BadPromotion("".to_string())));
+    }
+
+    #[test]
+    fn parses_commands_in_every_notation() {
+        let chess_board = ChessBoard::new();
+        for command in &["Move E2,E4", "e2e4", "Move E2E4", "e4", "Move e4"] {
+            let op = parse_command(command, &chess_board).unwrap();
+            assert_eq!((op.from, op.to), (12, 28), "{}", command);
+        }
+        assert_eq!(parse_command("  ", &chess_board).err(), Some(ParseError::Empty));
+        assert_eq!(parse_command("Move", &chess_board).err(), Some(ParseError::MissingMove));
+        assert_eq!(parse_command("Move E2", &chess_board).err(), Some(ParseError::MissingDestination));
+        assert_eq!(parse_command("Jump e4", &chess_board).err(), Some(ParseError::UnknownOp("Jump".to_string())));
+        assert_eq!(
+            parse_command("Nf6", &chess_board).err(),
+            Some(ParseError::San(SanError::Illegal("Nf6".to_string())))
+        );
+        assert_eq!(
+            parse_command("Move e4 e5", &chess_board).err(),
+            Some(ParseError::San(SanError::Invalid("Move e4 e5".to_string())))
+        );
+    }
+
+    #[test]
+    fn explains_why_a_move_is_not_legal() {
+        let chess_board = board("4k3/P7/8/8/8/8/3r4/4K3 w - - 0 1");
+        let resolve = |command: &str| resolve_move(&parse_command(command, &chess_board).unwrap(), &chess_board);
+        assert_eq!(resolve("Move E1,D2"), Ok(ChessMove::new(4, 11)));
+        assert_eq!(resolve("Move C3,C4"), Err(MoveError::EmptySquare(18)));
+        assert_eq!(resolve("Move D2,D1"), Err(MoveError::WrongSideToMove));
+        assert_eq!(resolve("Move E1,E3"), Err(MoveError::Illegal));
+        assert_eq!(resolve("Move E1,E2"), Err(MoveError::LeavesKingInCheck));
+        assert_eq!(resolve("Move A7,A8"), Err(MoveError::MissingPromotion));
+        assert_eq!(resolve("a7a8"), Err(MoveError::MissingPromotion));
+        assert_eq!(resolve("a8"), Err(MoveError::MissingPromotion));
+        assert_eq!(resolve("Move E1,D2=Q"), Err(MoveError::UnexpectedPromotion));
+    }
+
+    #[test]
+    fn play_command_leaves_the_board_alone_on_error() {
+        let mut chess_board = ChessBoard::new();
+        assert!(play_command("Move E2,E5", &mut chess_board).is_err());
+        assert_eq!(chess_board.to_fen(), STARTING_FEN);
+        let undo = play_command("e4", &mut chess_board).unwrap();
+        assert_eq!(undo.chess_move(), ChessMove::new(12, 28));
+        assert_eq!(chess_board.turn(), Player::Black);
+    }
+}
@@ -1,607 +1,293 @@
+use std::env;
 use std::fs;
+use std::io::{self, IsTerminal};
+use std::process;
+use std::time::Instant;
+
+use chess::{
+    evaluation, parse_square, run_script, ChessBoard, ChessMove, Player, SearchLimits, SearchResult, Searcher,
+    DEFAULT_HASH_MB, MAX_HASH_MB, PERFT_SUITE,
+};
+
+const USAGE: &str = "Usage: chess <game script>
+       chess svg <output.svg> [--fen <FEN>] [--flip] [--no-coordinates]
+                 [--last-move <e2e4>] [--arrow <e2e4>]... [--circle <e4>]...
+       chess perft <depth> [--fen <FEN>] [--divide]
+       chess perft --suite [max depth]
+       chess bench [depth]
+       chess bench --search [depth]
+       chess search [--fen <FEN>] [--depth <plies>] [--nodes <count>] [--hash <MB>]
+       chess eval [FEN]";
 
-#[derive(Copy, Clone)]
-pub enum Piece {
-    King,
-    Queen,
-    Knight,
-    Bishop,
-    Rook,
-    Pawn
-}
-
-#[derive(PartialEq, Copy, Clone)]
-pub enum Player {
-    Black,
-    White
-}
-
-#[derive(Copy, Clone)]
-pub struct ChessPiece {
-    piece: Piece,
-    player: Player 
-}
-
-#[derive(Copy, Clone)]
-pub struct Square {
-    value: Option<ChessPiece>,
-}
-
-impl Square {
-    fn new() -> Self {
-        Square {
-            value: None
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("svg") => export_svg(&args[1..]),
+        Some("perft") => perft(&args[1..]),
+        Some("search") => search(&args[1..]),
+        Some("eval") => evaluate(args.get(1)),
+        Some("bench") if args.get(1).map(String::as_str) == Some("--search") => {
+            bench_search(args.get(2).map_or(5, |depth| parse_depth(depth)))
+        }
+        Some("bench") => bench(args.get(1).map_or(4, |depth| parse_depth(depth))),
+        Some(path) => play_script(path),
+        None => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
         }
     }
-
-    pub fn init(&mut self, piece: ChessPiece){
-        self.value = Some(piece);
-    }
-}
-
-pub struct ChessBoard {
-    board: [Square; 64], 
 }
 
-impl ChessBoard {
-    fn new() -> Self {
-        let mut chess_board = [Square::new(); 64];
-        chess_board[0].init(ChessPiece {
-            piece: Piece::Rook,
-            player: Player::White
-        });
-        chess_board[7].init(ChessPiece {
-            piece: Piece::Rook,
-            player: Player::White
-        });
-
-        chess_board[1].init(ChessPiece {
-            piece: Piece::Knight,
-            player: Player::White
-        });
-        chess_board[6].init(ChessPiece {
-            piece: Piece::Knight,
-            player: Player::White
-        });
-
-        chess_board[2].init(ChessPiece {
-            piece: Piece::Bishop,
-            player: Player::White
-        });
-        chess_board[5].init(ChessPiece {
-            piece: Piece::Bishop,
-            player: Player::White
-        });
-
-        chess_board[3].init(ChessPiece {
-            piece: Piece::Queen,
-            player: Player::White
-        });
-        chess_board[4].init(ChessPiece {
-            piece: Piece::King,
-            player: Player::White
-        });
-
-
-        chess_board[56].init(ChessPiece {
-            piece: Piece::Rook,
-            player: Player::Black
-        });
-        chess_board[63].init(ChessPiece {
-            piece: Piece::Rook,
-            player: Player::Black
-        });
-
-        chess_board[57].init(ChessPiece {
-            piece: Piece::Knight,
-            player: Player::Black
-        });
-        chess_board[62].init(ChessPiece {
-            piece: Piece::Knight,
-            player: Player::Black
-        });
-
-        chess_board[58].init(ChessPiece {
-            piece: Piece::Bishop,
-            player: Player::Black
-        });
-        chess_board[61].init(ChessPiece {
-            piece: Piece::Bishop,
-            player: Player::Black
-        });
-
-        chess_board[59].init(ChessPiece {
-            piece: Piece::Queen,
-            player: Player::Black
-        });
-        chess_board[60].init(ChessPiece {
-            piece: Piece::King,
-            player: Player::Black
-        });
-
-        for idx in 8..15 {
-            chess_board[idx].init(ChessPiece {
-                piece: Piece::Pawn,
-                player: Player::White
-            })
-        }
-
-        for idx in 48..55 {
-            chess_board[idx].init(ChessPiece {
-                piece: Piece::Pawn,
-                player: Player::Black
-            });
+fn play_script(path: &str) {
+    let contents = match fs::read_to_string(path) {
+        Err(why) => {
+            eprintln!("Couldn't read {} because {}", path, why);
+            process::exit(1);
         }
+        Ok(content) => content
+    };
 
-        ChessBoard {
-            board: chess_board
-        }
+    let mut chess_board = ChessBoard::new();
+    let outcome = run_script(&contents, &mut chess_board);
+    print!("{}", chess_board.view().colour(io::stdout().is_terminal()));
+    println!("{}", chess_board.to_fen());
+    println!("{}", chess_board.status());
+    if let Err(why) = outcome {
+        eprintln!("{}: {}", path, why);
+        process::exit(1);
     }
 }
 
-#[derive(Debug)]
-pub struct Operation {
-    op: String,
-    from: usize,
-    to: usize,
-}
-
-pub fn parse(content: &str) -> Operation {
-    let mut moves = content.split_whitespace();
-    let op = match moves.next() {
-        Some(valid_op) => valid_op.split_whitespace().collect(),
-        None => panic!("Something wrong")
-    };
-    let mut directions = match moves.next() {
-        Some(valid_direction) => valid_direction.split(","),
-        None => panic!("Something wrong")
-    };
-    let start = match directions.next() {
-        Some(start) => start,
-        None => panic!("Something wrong")
+fn export_svg(args: &[String]) {
+    let output = match args.first() {
+        Some(output) if !output.starts_with("--") => output,
+        _ => usage_error("missing output file"),
     };
-    let dest = match directions.next() {
-        Some(dest) => dest,
-        None => panic!("Something wrong")
-    };
-    let from_idx = convert_to_idx(&start);
-    let to_idx = convert_to_idx(&dest);
-    return Operation {
-        op: op,
-        from: from_idx,
-        to: to_idx
+    let mut chess_board = ChessBoard::new();
+    let mut orientation = Player::White;
+    let mut coordinates = true;
+    let mut last_move = None;
+    let mut arrows = Vec::new();
+    let mut circles = Vec::new();
+
+    let mut options = args[1..].iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--flip" => orientation = Player::Black,
+            "--no-coordinates" => coordinates = false,
+            "--fen" | "--last-move" | "--arrow" | "--circle" => {
+                let value = match options.next() {
+                    Some(value) => value,
+                    None => usage_error(&format!("{} needs a value", option)),
+                };
+                match option.as_str() {
+                    "--fen" => {
+                        chess_board = ChessBoard::from_fen(value).unwrap_or_else(|why| usage_error(&why.to_string()))
+                    }
+                    "--last-move" => last_move = Some(parse_uci(value)),
+                    "--arrow" => arrows.push(parse_uci(value)),
+                    _ => circles.push(parse_square(value).unwrap_or_else(|| usage_error(&format!("'{}' is not a square", value)))),
+                }
+            }
+            _ => usage_error(&format!("unknown option {}", option)),
+        }
     }
-}
-
-pub fn convert_to_idx(chess_move: &str) -> usize {
-    let mut chars = chess_move.chars();
-    let start = 'A' as u32;
-    let col = chars.next().unwrap() as u32;
-    let row = match chars.next().unwrap().to_digit(10) {
-        Some(val) => val,
-        None => panic!("Something wrong")
-    };
-    return ((col - start) + (row - 1)* 8) as usize;
-}
 
-pub fn validate_move(op: Operation, chess_board: ChessBoard) -> bool {
-    if op.from >= 64 || op.to >= 64 {
-        return false
+    let mut diagram = chess_board.svg().orientation(orientation).coordinates(coordinates).last_move(last_move);
+    for arrow in arrows {
+        diagram = diagram.arrow(arrow.from, arrow.to);
     }
-    let from = op.from;
-    let to = op.to;
-    let from_piece = chess_board.board[from].value;
-    let to_piece = chess_board.board[to].value;
-    match (from_piece, to_piece)  {
-        (Some(a), None) => return validate_move_by_piece(&chess_board, a, from, to),
-        (Some(a), Some(b)) => return validate_move_by_pieces(&chess_board, a, b, from, to),
-        (_, _) => false,
+    for square in circles {
+        diagram = diagram.circle(square);
     }
-}
-
-pub fn validate_move_by_piece(chess_board: &ChessBoard, chess_piece: ChessPiece, from: usize, to: usize) -> bool {
-    let current_piece = chess_piece.piece;
-    return match current_piece {
-        Piece::Pawn => (from + 8) == to,
-        Piece::King => (from + 8) == to,
-        Piece::Rook => return validate_horizontal_or_vertical(chess_board, from, to),
-        Piece::Knight => return validate_l_shape(from, to),
-        Piece::Bishop => return validate_diagonals(chess_board, from, to),
-        Piece::Queen => return validate_diagonals(chess_board, from, to) && validate_horizontal_or_vertical(chess_board, from, to),
-    };
-}
-
-pub fn validate_move_by_pieces(chess_board: &ChessBoard, from_piece: ChessPiece, to_piece: ChessPiece, from: usize, to: usize) -> bool {
-    let from_piece_val = from_piece.piece;
-    let to_piece_val = to_piece.piece;
-    let from_piece_player = from_piece.player;
-    let to_piece_player = to_piece.player;
-    return match (from_piece_val, to_piece_val, from_piece_player, to_piece_player) {
-        (Piece::Pawn, Piece::Pawn, Player::White, Player::Black) => {
-            return (get_col(from) + 1 == get_col(to) && get_row(from) + 1 == get_row(to)) ||
-                    (get_col(from) - 1 == get_col(to) && get_row(from) + 1 == get_row(to))
-        },
-        (Piece::Pawn, Piece::Pawn, Player::Black, Player::White) => {
-            return (get_col(from) + 1 == get_col(to) && get_row(from) -1 == get_row(to)) ||
-                    (get_col(from) - 1 == get_col(to) && get_row(from) - 1 == get_row(to))
-        },
-        (Piece::King, _, _, _) => {
-            return (get_col(from) + 1 == get_col(to) && get_row(from) + 1 == get_row(to)) ||
-                    (get_col(from) - 1 == get_col(to) && get_row(from) + 1 == get_row(to))
-        },
-        (Piece::Queen, _, _, _) => {
-            return validate_diagonals(chess_board, from, to) && validate_horizontal_or_vertical(chess_board, from, to);
-        },
-        (Piece::Bishop, _, _, _) => {
-            return validate_diagonals(chess_board, from, to);
-        },
-        (Piece::Knight, _, _, _) => {
-            return validate_l_shape(from, to);
-        },
-        (Piece::Rook, _, _, _) => {
-            return validate_horizontal_or_vertical(chess_board, from, to);
-        },
-        (_, _, Player::Black, Player::Black) => false,
-        (_, _, Player::White, Player::White) => false, 
-        (_, _, _, _) => false,
+    if let Err(why) = fs::write(output, diagram.to_svg()) {
+        eprintln!("Couldn't write {} because {}", output, why);
+        process::exit(1);
     }
 }
 
-pub fn validate_l_shape(from: usize, to:usize) -> bool {
-    let from_col = get_col(from);
-    let from_row = get_row(from);
-    let to_col = get_col(to);
-    let to_row = get_row(to);
-    return
-        (to_col - from_col == 1 && to_row - to_col == 2) ||
-        (to_col - from_col == 1 && to_col - to_row == 2) || 
-        (to_col - from_col == 2 && to_row - from_row == 1) || 
-        (to_col - from_col == 2 && from_row - to_row == 1) || 
-        (from_col - to_col == 2 && to_row - from_row == 1) ||
-        (from_col - to_col == 2 && from_row - to_row == 1) || 
-        (from_col - to_col == 1 && from_row - to_row == 2) || 
-        (from_col - to_col == 1 && to_row - from_row == 2)
-}
-
-pub fn validate_diagonals(chess_board: &ChessBoard, from: usize, to: usize) -> bool {
-    let board = (*chess_board).board;
-    let from_col = get_col(from);
-    let from_row = get_row(from);
-    let to_col = get_col(to);
-    let to_row = get_row(to);
-    if to_col - from_col != to_row - from_row && 
-        from_col - to_col != from_row - to_row &&
-        to_col - from_col != from_row - to_row &&
-        from_col - to_col != to_row - from_row {
-            return false;
+fn perft(args: &[String]) {
+    if args.first().map(String::as_str) == Some("--suite") {
+        let max_depth = args.get(1).map_or(3, |depth| parse_depth(depth));
+        return perft_suite(max_depth);
     }
-
-    if from_row < to_row && from_col < to_col {
-        for (i, j) in (from_row..to_row).zip(from_col..to_col) {
-            let is_unblocked = match board[to_idx(i, j)].value {
-                Some(_) => false,
-                None => true,
-            };
-            if !is_unblocked {
-                return is_unblocked;
-            }
-        }
-    } else if from_row > to_row && from_col > to_col {
-        for (i, j) in (to_row..from_row).zip(to_col..from_col) {
-            let is_unblocked = match board[to_idx(i, j)].value {
-                Some(_) => false,
-                None => true
-            };
-            if !is_unblocked {
-                return is_unblocked;
-            }
-        }
-    } else if from_row < to_row && from_col > to_col {
-        for (i, j) in (from_row..to_row).zip(to_col..from_col) {
-            let is_unblocked = match board[to_idx(i, j)].value {
-                Some(_) => false,
-                None => true
-            };
-            if !is_unblocked {
-                return is_unblocked;
-            }
-        }
-    } else if from_row > to_row && from_col < to_col {
-        for (i, j) in (to_row..from_row).zip(from_col..to_col) {
-            let is_unblocked = match board[to_idx(i, j)].value {
-                Some(_) => false,
-                None => false,
-            };
-            if !is_unblocked {
-                return is_unblocked;
+    let depth = match args.first() {
+        Some(depth) => parse_depth(depth),
+        None => usage_error("missing depth"),
+    };
+    let mut chess_board = ChessBoard::new();
+    let mut divide = false;
+    let mut options = args[1..].iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--divide" => divide = true,
+            "--fen" => {
+                let fen = options.next().unwrap_or_else(|| usage_error("--fen needs a value"));
+                chess_board = ChessBoard::from_fen(fen).unwrap_or_else(|why| usage_error(&why.to_string()));
             }
+            _ => usage_error(&format!("unknown option {}", option)),
         }
     }
-    return true;
-}
-
-pub fn validate_horizontal_or_vertical(chess_board: &ChessBoard, from: usize, to: usize) -> bool {
-    let board = (*chess_board).board;
-    let from_col = get_col(from);
-    let from_row = get_row(from);
-    let to_row = get_row(to);
-    let to_col = get_col(to);
-    if from_col == to_col {
-        if from_row < to_row {
-            for i in from_row..to_row {
-                let is_unblocked = match board[to_idx(i, from_col)].value {
-                    Some(_) => false,
-                    None => true,
-                };
-                if !is_unblocked {
-                    return is_unblocked;
-                }
-            }
-        } else if from_row > to_row {
-            for i in to_row..from_row {
-                let is_unblocked = match board[to_idx(i, from_col)].value {
-                    Some(_) => false,
-                    None => true,
-                };
-                if !is_unblocked {
-                    return is_unblocked;
-                }
-            }
-        }
-    } else if from_row == to_row {
-        if from_col < to_col {
-            for i in from_col..to_col {
-                let is_unblocked = match board[to_idx(from_row, i)].value {
-                    Some(_) => false,
-                    None => true,
-                };
-                if !is_unblocked {
-                    return is_unblocked
-                }
-            }
-        } else if to_col < from_col {
-            for i in to_col..from_col {
-                let is_unblocked = match board[to_idx(from_row, i)].value {
-                    Some(_) => false,
-                    None => true,
-                };
-                if !is_unblocked {
-                    return is_unblocked
-                }
-            }
-        } 
+    if divide && depth == 0 {
+        usage_error("--divide needs a depth of at least 1");
     }
-    return true;
-}
 
-pub fn execute_move(op: Operation, chess_board: &ChessBoard) {
-    let from = op.from;
-    let to = op.to;
-    let mut board = (*chess_board).board;
-    let from_piece = board[from].value;
-    let to_piece = board[to].value;
-    match (from_piece, to_piece) {
-        (Some(a), Some(b)) => {
-            match (a.piece, b.piece) {
-              (_, Piece::King) => (),
-              (_, _) => {
-                  board[to] = board[from];
-              }
-            };
-        },
-        (Some(_), None) => {
-            board[to] = board[from];
-        },
-        (_, _) => ()
+    let start = Instant::now();
+    let nodes = if divide {
+        let counts = chess_board.divide(depth);
+        for (chess_move, count) in &counts {
+            println!("{}: {}", chess_move, count);
+        }
+        println!();
+        counts.iter().map(|(_, count)| count).sum()
+    } else {
+        chess_board.perft(depth)
     };
+    let elapsed = start.elapsed().as_secs_f64();
+    println!("{} nodes in {:.3}s ({:.0} nodes/s)", nodes, elapsed, nodes as f64 / elapsed.max(1e-9));
 }
 
-pub fn is_checked(op: Operation ,chess_board: &ChessBoard) -> bool {
-    let from = op.from;
-    let to = op.to;
-    let board = (*chess_board).board;
-    let from_piece = board[from].value;
-    let to_piece = board[to].value;
-    return match (from_piece, to_piece) {
-        (_, Some(a)) => {
-            return match a.piece {
-                Piece::King => true,
-                _ => false,
+fn perft_suite(max_depth: u32) {
+    let mut failures = 0;
+    for position in &PERFT_SUITE {
+        let chess_board = ChessBoard::from_fen(position.fen).expect("suite positions are valid FEN");
+        for (depth, &expected) in (1..=max_depth).zip(position.nodes) {
+            let start = Instant::now();
+            let nodes = chess_board.perft(depth);
+            let verdict = if nodes == expected { "ok" } else { "FAILED" };
+            println!(
+                "{:<12} depth {}: {:>10} nodes, expected {:>10} in {:.3}s  {}",
+                position.name,
+                depth,
+                nodes,
+                expected,
+                start.elapsed().as_secs_f64(),
+                verdict
+            );
+            if nodes != expected {
+                failures += 1;
             }
-        },
-        (_, _) => false
-    };
-}
-
-pub fn is_empty_square(chess_board: &ChessBoard, row: usize, col: usize) -> bool {
-    let square_idx = to_idx(row, col);
-    let board = (*chess_board).board;
-    if square_idx < 64 && square_idx >= 0 {
-        return match board[square_idx].value {
-            Some(_) => false,
-            None => true,
-        };
+        }
+    }
+    if failures > 0 {
+        eprintln!("{} perft counts differ from the reference", failures);
+        process::exit(1);
     }
-    return false;
-}
-
-pub fn is_checkmated(op: Operation, chess_board: &ChessBoard) -> bool {
-    let from = op.from;
-    let to = op.to;
-    let board = (*chess_board).board;
-    let from_piece = board[from].value;
-    let to_piece = board[to].value;
-    let to_row = get_row(to);
-    let to_col = get_col(to);
-    return match (from_piece, to_piece) {
-        (_, Some(a)) => {
-            // check if king is surrounded
-            match a.piece {
-                Piece::King => {
-                    let is_up_empty = is_empty_square(chess_board, to_row + 1, to_col);
-                    let is_down_empty = is_empty_square(chess_board, to_row - 1, to_col);
-                    let is_left_empty = is_empty_square(chess_board, to_row, to_col - 1);
-                    let is_right_empty = is_empty_square(chess_board, to_row, to_col + 1);
-                    let is_left_up_empty = is_empty_square(chess_board, to_row + 1, to_col - 1);
-                    let is_left_down_empty = is_empty_square(chess_board, to_row - 1, to_col - 1);
-                    let is_right_up_empty = is_empty_square(chess_board, to_row + 1, to_col + 1);
-                    let is_right_down_empty = is_empty_square(chess_board, to_row - 1, to_col + 1);
-                    if !is_up_empty && !is_down_empty && !is_left_empty && !is_right_empty && 
-                        !is_left_up_empty && !is_left_down_empty && !is_right_up_empty && !is_right_down_empty {
-                        return true;
-                    }
-
-                    let curr_player = a.player;
-                    return is_checked_square(chess_board, to_row + 1, to_col, curr_player) &&
-                        is_checked_square(chess_board, to_row - 1, to_col, curr_player) &&
-                        is_checked_square(chess_board, to_row, to_col + 1, curr_player) &&
-                        is_checked_square(chess_board, to_row, to_col - 1, curr_player) &&
-                        is_checked_square(chess_board, to_row + 1, to_col + 1, curr_player) &&
-                        is_checked_square(chess_board, to_row + 1, to_col - 1, curr_player) &&
-                        is_checked_square(chess_board, to_row - 1, to_col + 1, curr_player) &&
-                        is_checked_square(chess_board, to_row - 1, to_col - 1, curr_player);
-                },
-                _ => false
-            }
-        },
-        (_, _) => false
-    };
 }
 
-pub fn is_checked_square(chess_board: &ChessBoard, row: usize, col: usize, player: Player) -> bool {
-    return is_checked_horizontal(chess_board, row, col, player) &&
-        is_checked_vertical(chess_board, row, col, player) &&
-        is_checked_diagonal(chess_board, row, col, player) &&
-        is_checked_l_shape(chess_board, row, col, player);
+/// Times perft on the reference positions, as a measure of move generation speed.
+fn bench(depth: u32) {
+    let mut total_nodes = 0;
+    let mut total_time = 0.0;
+    for position in &PERFT_SUITE {
+        let chess_board = ChessBoard::from_fen(position.fen).expect("suite positions are valid FEN");
+        let start = Instant::now();
+        let nodes = chess_board.perft(depth);
+        let elapsed = start.elapsed().as_secs_f64();
+        println!("{:<12} {:>10} nodes in {:.3}s ({:.0} nodes/s)", position.name, nodes, elapsed, nodes as f64 / elapsed.max(1e-9));
+        total_nodes += nodes;
+        total_time += elapsed;
+    }
+    println!("{:<12} {:>10} nodes in {:.3}s ({:.0} nodes/s)", "total", total_nodes, total_time, total_nodes as f64 / total_time.max(1e-9));
 }
 
-pub fn is_checked_horizontal(chess_board: &ChessBoard, row: usize, col: usize, player: Player) -> bool {
-    let board = (*chess_board).board;
-    let piece = board[to_idx(row, col)].value;
-    match piece {
-        Some(a) => {
-            match a.piece {
-                Piece::Rook => {
-                    return a.player != player
-                },
-                Piece::Queen => {
-                    return a.player != player
-                },
-                _ => return false
+fn search(args: &[String]) {
+    let mut chess_board = ChessBoard::new();
+    let mut limits = SearchLimits::default();
+    let mut hash_size = DEFAULT_HASH_MB;
+    let mut options = args.iter();
+    while let Some(option) = options.next() {
+        let value = options.next().unwrap_or_else(|| usage_error(&format!("{} needs a value", option)));
+        match option.as_str() {
+            "--fen" => chess_board = ChessBoard::from_fen(value).unwrap_or_else(|why| usage_error(&why.to_string())),
+            "--depth" => limits.depth = Some(parse_depth(value)),
+            "--nodes" => {
+                limits.nodes = Some(value.parse().unwrap_or_else(|_| usage_error(&format!("'{}' is not a node count", value))))
             }
-        },
-        None => {
-            if row == 0 || col == 0 || row == 7 || col == 7 {
-                return false;
+            "--hash" => {
+                hash_size = match value.parse() {
+                    Ok(size) if (1..=MAX_HASH_MB).contains(&size) => size,
+                    _ => usage_error(&format!("'{}' is not a size between 1 and {} MB", value, MAX_HASH_MB)),
+                }
             }
-            return is_checked_horizontal(chess_board, row, col - 1, player) ||
-                is_checked_horizontal(chess_board, row, col + 1, player);
+            _ => usage_error(&format!("unknown option {}", option)),
         }
     }
-}
+    if limits.depth.is_none() && limits.nodes.is_none() {
+        limits.depth = Some(5);
+    }
 
-pub fn is_checked_vertical(chess_board: &ChessBoard, row: usize, col: usize, player: Player) -> bool {
-    let board = (*chess_board).board;
-    let piece = board[to_idx(row, col)].value;
-    match piece {
-        Some(a) => {
-            match a.piece {
-                Piece::Rook => {
-                    return a.player != player
-                },
-                Piece::Queen => {
-                    return a.player != player
-                },
-                _ => return false,
-            }
-        },
-        None => {
-            if row == 0 || col == 0 || row == 7 || col == 7 {
-                return false;
-            }
-            return is_checked_vertical(chess_board, row + 1, col, player) ||
-                is_checked_vertical(chess_board, row - 1, col, player);
-        }
-    };
+    let start = Instant::now();
+    let result = Searcher::with_hash_size(hash_size).search(&chess_board, &limits, |result| {
+        let pv: Vec<String> = result.pv.iter().map(ChessMove::to_uci).collect();
+        println!(
+            "depth {} score {} nodes {} time {:.3}s pv {}",
+            result.depth,
+            format_score(result),
+            result.nodes,
+            start.elapsed().as_secs_f64(),
+            pv.join(" ")
+        );
+    });
+    match result.best_move {
+        Some(chess_move) => println!("bestmove {}", chess_move),
+        None => println!("bestmove (none)"),
+    }
 }
 
-pub fn is_checked_diagonal(chess_board: &ChessBoard, row: usize, col: usize, player: Player) -> bool {
-    let board = (*chess_board).board;
-    let piece = board[to_idx(row, col)].value;
-    match piece {
-        Some(a) => {
-            match a.piece {
-                Piece::Pawn => {
-                    return a.player != player
-                },
-                Piece::Bishop => {
-                    return a.player != player
-                },
-                Piece::Queen => {
-                    return a.player != player
-                },
-                _ => return false,
-            }
-        },
-        None => {
-            if row == 0 || col == 0 || row == 7 || col == 7 {
-                return false;
-            }
-            return is_checked_diagonal(chess_board, row + 1, col + 1, player)||
-                is_checked_diagonal(chess_board, row + 1, col - 1, player) ||
-                is_checked_diagonal(chess_board, row - 1, col + 1, player) ||
-                is_checked_diagonal(chess_board, row - 1, col - 1, player);
-        }
+/// Prints the terms of the static evaluation of a position.
+fn evaluate(fen: Option<&String>) {
+    let chess_board = match fen {
+        Some(fen) => ChessBoard::from_fen(fen).unwrap_or_else(|why| usage_error(&why.to_string())),
+        None => ChessBoard::new(),
     };
+    print!("{}", chess_board.view().colour(io::stdout().is_terminal()));
+    println!("{}", evaluation(&chess_board));
 }
 
-pub fn is_checked_l_shape(chess_board: &ChessBoard, row: usize, col: usize, player: Player) -> bool {
-    let board = (*chess_board).board;
-    let piece = board[to_idx(row, col)].value;
-    match piece {
-        Some(a) => {
-            match a.piece {
-                Piece::Knight => {
-                    return a.player != player
-                },
-                _ => return false,
-            }
-        },
-        None => {
-            if row == 0 || col == 0 || row == 7 || col == 7 {
-                return false;
-            }
-            return is_checked_l_shape(chess_board, row + 2, col + 1, player)||
-                is_checked_l_shape(chess_board, row + 2, col - 1, player) ||
-                is_checked_l_shape(chess_board, row + 1, col + 2, player) ||
-                is_checked_l_shape(chess_board, row - 1, col + 2, player) ||
-                is_checked_l_shape(chess_board, row + 1, col - 2, player) || 
-                is_checked_l_shape(chess_board, row - 1, col - 2, player) ||
-                is_checked_l_shape(chess_board, row - 2, col - 1, player) || 
-                is_checked_l_shape(chess_board, row - 2, col + 1, player);
-        }
-    };
+fn format_score(result: &SearchResult) -> String {
+    match result.mate_in() {
+        Some(moves) => format!("mate {}", moves),
+        None => format!("cp {}", result.score),
+    }
 }
 
-
-
-pub fn get_col(idx: usize) -> usize {
-    return (idx - (idx % 8)) / 8;
+/// Searches the reference positions to a fixed depth, to compare node counts between versions of
+/// the search.
+fn bench_search(depth: u32) {
+    let limits = SearchLimits {
+        depth: Some(depth),
+        ..SearchLimits::default()
+    };
+    let mut total_nodes = 0;
+    let start = Instant::now();
+    for position in &PERFT_SUITE {
+        let chess_board = ChessBoard::from_fen(position.fen).expect("suite positions are valid FEN");
+        let position_start = Instant::now();
+        let result = Searcher::new().search(&chess_board, &limits, |_| ());
+        let best_move = result.best_move.map_or("(none)".to_string(), |chess_move| chess_move.to_uci());
+        println!(
+            "{:<12} {:>10} nodes in {:.3}s, best move {} score {}",
+            position.name,
+            result.nodes,
+            position_start.elapsed().as_secs_f64(),
+            best_move,
+            format_score(&result)
+        );
+        total_nodes += result.nodes;
+    }
+    println!("{:<12} {:>10} nodes in {:.3}s", "total", total_nodes, start.elapsed().as_secs_f64());
 }
 
-pub fn get_row(idx: usize) -> usize {
-    return idx % 8;
+fn parse_depth(text: &str) -> u32 {
+    text.parse().unwrap_or_else(|_| usage_error(&format!("'{}' is not a depth", text)))
 }
 
-pub fn to_idx(row: usize, col: usize) -> usize {
-    return row * 8 + col;
+fn parse_uci(text: &str) -> ChessMove {
+    ChessMove::from_uci(text).unwrap_or_else(|why| usage_error(&why.to_string()))
 }
 
-fn main() {
-    let contents = match fs::read_to_string("./inputs/game1.txt") {
-        Err(why) => panic!("Couldn't read file because {}", why),
-        Ok(content) => content
-    };
-    
-    let op = parse(&contents);
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}\n{}", message, USAGE);
+    process::exit(2);
 }
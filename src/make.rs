@@ -0,0 +1,202 @@
+use crate::{get_col, get_row, square_name, to_idx, CastlingRights, ChessBoard, ChessMove, ChessPiece, Piece, Player};
+
+/// Everything `unmake_move` needs to restore the position a move was made from.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Undo {
+    chess_move: ChessMove,
+    moved: ChessPiece,
+    captured: Option<ChessPiece>,
+    captured_square: usize,
+    castling: CastlingRights,
+    en_passant: Option<usize>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    hash: u64,
+}
+
+impl Undo {
+    pub fn chess_move(&self) -> ChessMove {
+        self.chess_move
+    }
+
+    pub fn captured(&self) -> Option<ChessPiece> {
+        self.captured
+    }
+}
+
+impl ChessBoard {
+    /// Moves a piece, relocating the rook when castling, removing the pawn taken en passant and
+    /// promoting pawns, and hands the turn to the opponent. Legality is not checked, so the move
+    /// should come from `legal_moves`. The returned record takes the move back with `unmake_move`.
+    ///
+    /// Panics if there is no piece on the source square.
+    pub fn make_move(&mut self, chess_move: ChessMove) -> Undo {
+        let moving = match self.board[chess_move.from].value {
+            Some(chess_piece) => chess_piece,
+            None => panic!("No piece on {} to move", square_name(chess_move.from)),
+        };
+        let passed_square = to_idx(get_row(chess_move.from), get_col(chess_move.to));
+        let is_en_passant = moving.piece == Piece::Pawn
+            && get_col(chess_move.from) != get_col(chess_move.to)
+            && self.board[chess_move.to].value.is_none()
+            && self.board[passed_square].value
+                == Some(ChessPiece {
+                    piece: Piece::Pawn,
+                    player: moving.player.opponent(),
+                });
+        let captured_square = if is_en_passant { passed_square } else { chess_move.to };
+        let undo = Undo {
+            chess_move,
+            moved: moving,
+            captured: self.board[captured_square].value,
+            captured_square,
+            castling: self.castling,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            hash: self.hash,
+        };
+        self.history.push(self.hash);
+        self.hash ^= self.state_key();
+
+        if moving.piece == Piece::Pawn || undo.captured.is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if moving.player == Player::Black {
+            self.fullmove_number += 1;
+        }
+
+        self.remove_piece(captured_square);
+        self.remove_piece(chess_move.from);
+        let placed = match chess_move.promotion {
+            Some(piece) if moving.piece == Piece::Pawn => ChessPiece {
+                piece,
+                player: moving.player,
+            },
+            _ => moving,
+        };
+        self.put_piece(chess_move.to, placed);
+
+        self.en_passant = None;
+        if moving.piece == Piece::Pawn && get_row(chess_move.from).abs_diff(get_row(chess_move.to)) == 2 {
+            self.en_passant = Some((chess_move.from + chess_move.to) / 2);
+        }
+
+        if moving.piece == Piece::King {
+            self.castling.revoke(moving.player);
+            if chess_move.to == chess_move.from + 2 {
+                self.move_piece(chess_move.from + 3, chess_move.from + 1);
+            } else if chess_move.to + 2 == chess_move.from {
+                self.move_piece(chess_move.from - 4, chess_move.from - 1);
+            }
+        }
+        self.castling.revoke_square(chess_move.from);
+        self.castling.revoke_square(chess_move.to);
+
+        self.turn = self.turn.opponent();
+        self.hash ^= self.state_key();
+        undo
+    }
+
+    /// Takes back the move recorded in `undo`, which must be the last move made on this board.
+    pub fn unmake_move(&mut self, undo: Undo) {
+        let chess_move = undo.chess_move;
+        self.turn = self.turn.opponent();
+
+        if undo.moved.piece == Piece::King {
+            if chess_move.to == chess_move.from + 2 {
+                self.move_piece(chess_move.from + 1, chess_move.from + 3);
+            } else if chess_move.to + 2 == chess_move.from {
+                self.move_piece(chess_move.from - 1, chess_move.from - 4);
+            }
+        }
+
+        self.remove_piece(chess_move.to);
+        self.put_piece(chess_move.from, undo.moved);
+        if let Some(captured) = undo.captured {
+            self.put_piece(undo.captured_square, captured);
+        }
+
+        self.castling = undo.castling;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.fullmove_number = undo.fullmove_number;
+        self.hash = undo.hash;
+        self.history.pop();
+    }
+
+    /// Places a piece on an empty square, keeping the square array, bitboards and key in step.
+    fn put_piece(&mut self, square: usize, chess_piece: ChessPiece) {
+        self.board[square].value = Some(chess_piece);
+        self.bitboards.toggle(square, chess_piece);
+        self.toggle_piece_key(square, chess_piece);
+    }
+
+    fn remove_piece(&mut self, square: usize) -> Option<ChessPiece> {
+        let removed = self.board[square].value.take();
+        if let Some(chess_piece) = removed {
+            self.bitboards.toggle(square, chess_piece);
+            self.toggle_piece_key(square, chess_piece);
+        }
+        removed
+    }
+
+    fn move_piece(&mut self, from: usize, to: usize) {
+        if let Some(chess_piece) = self.remove_piece(from) {
+            self.put_piece(to, chess_piece);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::Bitboards;
+
+    fn board(fen: &str) -> ChessBoard {
+        ChessBoard::from_fen(fen).unwrap()
+    }
+
+    #[test]
+    fn unmake_restores_every_move() {
+        for fen in &[
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ] {
+            let mut chess_board = board(fen);
+            for chess_move in chess_board.legal_moves() {
+                let undo = chess_board.make_move(chess_move);
+                assert_eq!(chess_board.bitboards, Bitboards::from_squares(&chess_board.board), "{}", chess_move);
+                chess_board.unmake_move(undo);
+                assert_eq!(chess_board.to_fen(), *fen, "{}", chess_move);
+                assert_eq!(chess_board.bitboards, Bitboards::from_squares(&chess_board.board), "{}", chess_move);
+                assert_eq!(chess_board.zobrist_key(), chess_board.compute_zobrist_key(), "{}", chess_move);
+                assert!(chess_board.history.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn special_moves() {
+        let mut chess_board = board("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+        let undo = chess_board.make_move(ChessMove::new(36, 45));
+        assert_eq!(undo.captured().map(|chess_piece| chess_piece.piece), Some(Piece::Pawn));
+        assert_eq!(chess_board.to_fen(), "rnbqkbnr/ppp1p1pp/5P2/3p4/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3");
+
+        let mut chess_board = board("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 3 10");
+        chess_board.make_move(ChessMove::new(4, 2));
+        assert_eq!(chess_board.to_fen(), "r3k2r/8/8/8/8/8/8/2KR3R b kq - 4 10");
+        chess_board.make_move(ChessMove::new(63, 7));
+        assert_eq!(chess_board.to_fen(), "r3k3/8/8/8/8/8/8/2KR3r w q - 0 11");
+
+        let mut chess_board = board("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+        chess_board.make_move(ChessMove {
+            promotion: Some(Piece::Knight),
+            ..ChessMove::new(49, 57)
+        });
+        assert_eq!(chess_board.to_fen(), "1N2k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    }
+}
@@ -0,0 +1,217 @@
+use crate::bitboard::{
+    bishop_attacks, bit, rook_attacks, squares, Bitboard, KING_ATTACKS, KNIGHT_ATTACKS, PAWN_ATTACKS,
+};
+use crate::{get_col, get_row, to_idx, ChessBoard, Piece, Player};
+
+/// A move from one square index to another, as produced by `ChessBoard::legal_moves`.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct ChessMove {
+    pub from: usize,
+    pub to: usize,
+    pub promotion: Option<Piece>,
+}
+
+impl ChessMove {
+    pub fn new(from: usize, to: usize) -> Self {
+        ChessMove {
+            from,
+            to,
+            promotion: None,
+        }
+    }
+}
+
+/// Pushes a pawn move, expanded into one move per promotion piece on the last rank.
+fn push_pawn_move(from: usize, to: usize, moves: &mut Vec<ChessMove>) {
+    if get_row(to) == 0 || get_row(to) == 7 {
+        for &piece in &Piece::PROMOTIONS {
+            moves.push(ChessMove {
+                from,
+                to,
+                promotion: Some(piece),
+            });
+        }
+    } else {
+        moves.push(ChessMove::new(from, to));
+    }
+}
+
+pub(crate) fn pawn_direction(player: Player) -> i32 {
+    match player {
+        Player::White => 1,
+        Player::Black => -1,
+    }
+}
+
+fn push_moves(from: usize, targets: Bitboard, moves: &mut Vec<ChessMove>) {
+    for to in squares(targets) {
+        moves.push(ChessMove::new(from, to));
+    }
+}
+
+impl ChessBoard {
+    /// Every legal move for the side to move.
+    pub fn legal_moves(&self) -> Vec<ChessMove> {
+        let mut moves = self.pseudo_legal_moves();
+        moves.retain(|&chess_move| self.is_legal(chess_move));
+        moves
+    }
+
+    /// The legal captures and promotions, which the quiescence search follows past the horizon.
+    pub(crate) fn legal_captures(&self) -> Vec<ChessMove> {
+        let mut moves = self.pseudo_legal_moves();
+        moves.retain(|&chess_move| {
+            (self.is_capture(chess_move) || chess_move.promotion.is_some()) && self.is_legal(chess_move)
+        });
+        moves
+    }
+
+    pub(crate) fn is_capture(&self, chess_move: ChessMove) -> bool {
+        self.board[chess_move.to].value.is_some()
+            || (self.en_passant_target() == Some(chess_move.to) && self.has_piece(chess_move.from, Piece::Pawn, self.turn))
+    }
+
+    /// The en passant square, as long as an enemy pawn stands in front of it to be captured.
+    fn en_passant_target(&self) -> Option<usize> {
+        self.en_passant.filter(|&square| {
+            let captured = square as i32 - 8 * pawn_direction(self.turn);
+            (0..64).contains(&captured) && self.has_piece(captured as usize, Piece::Pawn, self.turn.opponent())
+        })
+    }
+
+    /// Moves that follow the movement rules of each piece but may leave the own king in check.
+    pub(crate) fn pseudo_legal_moves(&self) -> Vec<ChessMove> {
+        let mut moves = Vec::with_capacity(48);
+        let occupied = self.bitboards.occupied();
+        let targets = !self.bitboards.player(self.turn);
+        let queens = self.bitboards.pieces(Piece::Queen, self.turn);
+
+        self.pawn_moves(&mut moves);
+        for from in squares(self.bitboards.pieces(Piece::Knight, self.turn)) {
+            push_moves(from, KNIGHT_ATTACKS[from] & targets, &mut moves);
+        }
+        for from in squares(self.bitboards.pieces(Piece::Bishop, self.turn) | queens) {
+            push_moves(from, bishop_attacks(from, occupied) & targets, &mut moves);
+        }
+        for from in squares(self.bitboards.pieces(Piece::Rook, self.turn) | queens) {
+            push_moves(from, rook_attacks(from, occupied) & targets, &mut moves);
+        }
+        if let Some(from) = self.king_square(self.turn) {
+            push_moves(from, KING_ATTACKS[from] & targets, &mut moves);
+            self.castling_moves(from, &mut moves);
+        }
+        moves
+    }
+
+    /// Whether a pseudo-legal move keeps the mover's king out of check, worked out on the
+    /// bitboards without playing the move.
+    fn is_legal(&self, chess_move: ChessMove) -> bool {
+        let from = bit(chess_move.from);
+        let to = bit(chess_move.to);
+        let king = match self.king_square(self.turn) {
+            Some(square) if square == chess_move.from => chess_move.to,
+            Some(square) => square,
+            None => return true,
+        };
+        let is_pawn = self.has_piece(chess_move.from, Piece::Pawn, self.turn);
+        let captured = if is_pawn && self.en_passant_target() == Some(chess_move.to) && get_col(chess_move.from) != get_col(chess_move.to) {
+            bit(to_idx(get_row(chess_move.from), get_col(chess_move.to)))
+        } else {
+            to
+        };
+        let occupied = (self.bitboards.occupied() & !from & !captured) | to;
+        self.attackers(king, self.turn.opponent(), occupied) & !captured == 0
+    }
+
+    /// Castling is encoded as the king moving two files towards the rook.
+    fn castling_moves(&self, from: usize, moves: &mut Vec<ChessMove>) {
+        let home = match self.turn {
+            Player::White => 4,
+            Player::Black => 60,
+        };
+        let opponent = self.turn.opponent();
+        let occupied = self.bitboards.occupied();
+        if from != home || self.in_check(self.turn) {
+            return;
+        }
+        if self.castling.kingside(self.turn)
+            && self.has_piece(home + 3, Piece::Rook, self.turn)
+            && occupied & (bit(home + 1) | bit(home + 2)) == 0
+            && !self.is_square_attacked(home + 1, opponent)
+            && !self.is_square_attacked(home + 2, opponent)
+        {
+            moves.push(ChessMove::new(from, home + 2));
+        }
+        if self.castling.queenside(self.turn)
+            && self.has_piece(home - 4, Piece::Rook, self.turn)
+            && occupied & (bit(home - 1) | bit(home - 2) | bit(home - 3)) == 0
+            && !self.is_square_attacked(home - 1, opponent)
+            && !self.is_square_attacked(home - 2, opponent)
+        {
+            moves.push(ChessMove::new(from, home - 2));
+        }
+    }
+
+    fn pawn_moves(&self, moves: &mut Vec<ChessMove>) {
+        let step = 8 * pawn_direction(self.turn);
+        let start_row = match self.turn {
+            Player::White => 1,
+            Player::Black => 6,
+        };
+        let occupied = self.bitboards.occupied();
+        let targets = self.bitboards.player(self.turn.opponent()) | self.en_passant_target().map_or(0, bit);
+        for from in squares(self.bitboards.pieces(Piece::Pawn, self.turn)) {
+            // Pawns never stand on the last rank, so one step forward stays on the board.
+            let to = (from as i32 + step) as usize;
+            if occupied & bit(to) == 0 {
+                push_pawn_move(from, to, moves);
+                let double = (to as i32 + step) as usize;
+                if get_row(from) == start_row && occupied & bit(double) == 0 {
+                    moves.push(ChessMove::new(from, double));
+                }
+            }
+            for to in squares(PAWN_ATTACKS[self.turn as usize][from] & targets) {
+                push_pawn_move(from, to, moves);
+            }
+        }
+    }
+
+    fn has_piece(&self, square: usize, piece: Piece, player: Player) -> bool {
+        self.bitboards.pieces(piece, player) & bit(square) != 0
+    }
+
+    /// The pieces of `by_player` that attack `square` when the occupied squares are `occupied`.
+    pub(crate) fn attackers(&self, square: usize, by_player: Player, occupied: Bitboard) -> Bitboard {
+        let pieces = |piece| self.bitboards.pieces(piece, by_player);
+        let queens = pieces(Piece::Queen);
+        // A pawn attacks `square` from wherever a pawn of the other side on `square` would attack.
+        (PAWN_ATTACKS[by_player.opponent() as usize][square] & pieces(Piece::Pawn))
+            | (KNIGHT_ATTACKS[square] & pieces(Piece::Knight))
+            | (KING_ATTACKS[square] & pieces(Piece::King))
+            | (bishop_attacks(square, occupied) & (pieces(Piece::Bishop) | queens))
+            | (rook_attacks(square, occupied) & (pieces(Piece::Rook) | queens))
+    }
+
+    /// Whether any piece of `by_player` attacks `square`, following sliding pieces up to the
+    /// first blocker and pawns only in their capturing direction.
+    pub fn is_square_attacked(&self, square: usize, by_player: Player) -> bool {
+        self.attackers(square, by_player, self.bitboards.occupied()) != 0
+    }
+
+    pub(crate) fn king_square(&self, player: Player) -> Option<usize> {
+        let kings = self.bitboards.pieces(Piece::King, player);
+        if kings == 0 {
+            None
+        } else {
+            Some(kings.trailing_zeros() as usize)
+        }
+    }
+
+    /// Whether `player`'s king is attacked by the opponent.
+    pub fn in_check(&self, player: Player) -> bool {
+        match self.king_square(player) {
+            Some(square) => self.is_square_attacked(square, player.opponent()),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::cmp::Reverse;
+
+use crate::bitboard::bit;
+use crate::{ChessBoard, ChessMove, Piece, Player};
+
+const HASH_MOVE: i32 = 1_000_000;
+const GOOD_CAPTURE: i32 = 500_000;
+const KILLER: [i32; 2] = [400_000, 390_000];
+/// History scores are kept below this so that they never outrank a killer.
+const HISTORY_LIMIT: i32 = 300_000;
+const BAD_CAPTURE: i32 = -500_000;
+
+/// Exchanges are evaluated with the king worth more than everything else put together.
+fn exchange_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::King => 20_000,
+        piece => piece.value(),
+    }
+}
+
+impl ChessBoard {
+    /// The material the side to move wins (or loses, if negative) when it plays `chess_move` and
+    /// both sides then keep recapturing on the destination square with their least valuable
+    /// piece for as long as it pays.
+    pub fn static_exchange(&self, chess_move: ChessMove) -> i32 {
+        let moving = match self.board[chess_move.from].value {
+            Some(chess_piece) => chess_piece,
+            None => return 0,
+        };
+        let mut occupied = self.bitboards.occupied();
+        let captured = match self.board[chess_move.to].value {
+            Some(chess_piece) => exchange_value(chess_piece.piece),
+            None if self.is_capture(chess_move) => {
+                // En passant: the captured pawn is not on the destination square.
+                let pawn_square = if chess_move.to > chess_move.from { chess_move.to - 8 } else { chess_move.to + 8 };
+                occupied ^= bit(pawn_square);
+                Piece::Pawn.value()
+            }
+            None => 0,
+        };
+
+        // gains[i] is what the side making the i-th capture has gained if the exchange stops there.
+        let mut gains = [0; 32];
+        gains[0] = captured;
+        let mut on_square = exchange_value(moving.piece);
+        let mut from = bit(chess_move.from);
+        let mut side = moving.player;
+        let mut depth = 0;
+        while depth + 1 < gains.len() {
+            occupied ^= from;
+            side = side.opponent();
+            let attackers = self.attackers(chess_move.to, side, occupied) & occupied;
+            let (piece, square) = match self.least_valuable(attackers, side) {
+                Some(found) => found,
+                None => break,
+            };
+            depth += 1;
+            gains[depth] = on_square - gains[depth - 1];
+            on_square = exchange_value(piece);
+            from = bit(square);
+        }
+        // Each side may stop capturing instead when going on would leave it worse off.
+        while depth > 0 {
+            gains[depth - 1] = -(-gains[depth - 1]).max(gains[depth]);
+            depth -= 1;
+        }
+        gains[0]
+    }
+
+    fn least_valuable(&self, attackers: u64, player: Player) -> Option<(Piece, usize)> {
+        [Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen, Piece::King]
+            .iter()
+            .find_map(|&piece| {
+                let candidates = attackers & self.bitboards.pieces(piece, player);
+                if candidates == 0 {
+                    None
+                } else {
+                    Some((piece, candidates.trailing_zeros() as usize))
+                }
+            })
+    }
+
+    /// Most valuable victim, least valuable attacker: captures of big pieces by small ones first.
+    fn mvv_lva(&self, chess_move: ChessMove) -> i32 {
+        let victim = self.board[chess_move.to].value.map_or(Piece::Pawn.value(), |chess_piece| chess_piece.piece.value());
+        let attacker = self.board[chess_move.from].value.map_or(0, |chess_piece| chess_piece.piece.value());
+        let promotion = chess_move.promotion.map_or(0, Piece::value);
+        10 * (victim + promotion) - attacker
+    }
+}
+
+/// Puts captures and promotions in most valuable victim, least valuable attacker order for the
+/// quiescence search.
+pub(crate) fn order_captures(chess_board: &ChessBoard, moves: &mut [ChessMove]) {
+    moves.sort_by_cached_key(|&chess_move| Reverse(chess_board.mvv_lva(chess_move)));
+}
+
+/// What the search has learned about which moves tend to be good: killer moves that caused a
+/// cutoff at the same ply, and a history score for quiet moves that caused cutoffs anywhere.
+#[derive(Debug, Clone)]
+pub(crate) struct MoveOrdering {
+    killers: Vec<[Option<ChessMove>; 2]>,
+    /// Indexed by `Player as usize`, then the from and to squares.
+    history: Vec<[[i32; 64]; 64]>,
+}
+
+impl Default for MoveOrdering {
+    fn default() -> Self {
+        MoveOrdering {
+            killers: Vec::new(),
+            history: vec![[[0; 64]; 64]; 2],
+        }
+    }
+}
+
+impl MoveOrdering {
+    /// Sorts `moves` so that the hash move comes first, then winning and even captures by
+    /// MVV-LVA, killers, quiet moves by history and finally captures that lose material.
+    pub(crate) fn order(&self, chess_board: &ChessBoard, moves: &mut [ChessMove], hash_move: Option<ChessMove>, ply: usize) {
+        let killers = self.killers.get(ply).copied().unwrap_or([None, None]);
+        let history = &self.history[chess_board.turn as usize];
+        moves.sort_by_cached_key(|&chess_move| {
+            let score = if Some(chess_move) == hash_move {
+                HASH_MOVE
+            } else if chess_board.is_capture(chess_move) || chess_move.promotion.is_some() {
+                let mvv_lva = chess_board.mvv_lva(chess_move);
+                if chess_move.promotion.is_some() || chess_board.static_exchange(chess_move) >= 0 {
+                    GOOD_CAPTURE + mvv_lva
+                } else {
+                    BAD_CAPTURE + mvv_lva
+                }
+            } else if Some(chess_move) == killers[0] {
+                KILLER[0]
+            } else if Some(chess_move) == killers[1] {
+                KILLER[1]
+            } else {
+                history[chess_move.from][chess_move.to]
+            };
+            Reverse(score)
+        });
+    }
+
+    /// Records a quiet move that caused a beta cutoff.
+    pub(crate) fn record_cutoff(&mut self, player: Player, chess_move: ChessMove, depth: u32, ply: usize) {
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None, None]);
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(chess_move) {
+            killers[1] = killers[0];
+            killers[0] = Some(chess_move);
+        }
+
+        let history = &mut self.history[player as usize];
+        history[chess_move.from][chess_move.to] += (depth * depth) as i32;
+        if history[chess_move.from][chess_move.to] > HISTORY_LIMIT {
+            for scores in history.iter_mut() {
+                for score in scores.iter_mut() {
+                    *score /= 2;
+                }
+            }
+        }
+    }
+}
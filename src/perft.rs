@@ -0,0 +1,119 @@
+use crate::{ChessBoard, ChessMove};
+
+/// A position with its published perft node counts, starting at depth 1.
+#[derive(Debug, Copy, Clone)]
+pub struct PerftPosition {
+    pub name: &'static str,
+    pub fen: &'static str,
+    pub nodes: &'static [u64],
+}
+
+/// The reference positions from the Chess Programming Wiki's perft results page.
+pub const PERFT_SUITE: [PerftPosition; 6] = [
+    PerftPosition {
+        name: "start",
+        fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        nodes: &[20, 400, 8_902, 197_281, 4_865_609],
+    },
+    PerftPosition {
+        name: "kiwipete",
+        fen: "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        nodes: &[48, 2_039, 97_862, 4_085_603],
+    },
+    PerftPosition {
+        name: "position 3",
+        fen: "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        nodes: &[14, 191, 2_812, 43_238, 674_624],
+    },
+    PerftPosition {
+        name: "position 4",
+        fen: "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        nodes: &[6, 264, 9_467, 422_333],
+    },
+    PerftPosition {
+        name: "position 5",
+        fen: "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        nodes: &[44, 1_486, 62_379, 2_103_487],
+    },
+    PerftPosition {
+        name: "position 6",
+        fen: "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        nodes: &[46, 2_079, 89_890, 3_894_594],
+    },
+];
+
+impl ChessBoard {
+    /// Counts the leaf nodes of the legal move tree `depth` plies deep.
+    pub fn perft(&self, depth: u32) -> u64 {
+        let mut scratch = self.clone();
+        scratch.count_nodes(depth)
+    }
+
+    /// The perft count below each legal move, for tracking down where a move generator goes wrong.
+    /// At depth 0 no move is made, so the list is empty.
+    pub fn divide(&self, depth: u32) -> Vec<(ChessMove, u64)> {
+        let mut scratch = self.clone();
+        let mut counts = Vec::new();
+        if depth == 0 {
+            return counts;
+        }
+        for chess_move in self.legal_moves() {
+            let undo = scratch.make_move(chess_move);
+            counts.push((chess_move, scratch.count_nodes(depth - 1)));
+            scratch.unmake_move(undo);
+        }
+        counts
+    }
+
+    fn count_nodes(&mut self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = self.legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        let mut nodes = 0;
+        for chess_move in moves {
+            let undo = self.make_move(chess_move);
+            nodes += self.count_nodes(depth - 1);
+            self.unmake_move(undo);
+        }
+        nodes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks every depth of every suite position whose published count is at most `max_nodes`.
+    fn check_suite(max_nodes: u64) {
+        for position in &PERFT_SUITE {
+            let chess_board = ChessBoard::from_fen(position.fen).unwrap();
+            for (depth, &expected) in (1..).zip(position.nodes).take_while(|&(_, &nodes)| nodes <= max_nodes) {
+                assert_eq!(chess_board.perft(depth), expected, "{} at depth {}", position.name, depth);
+            }
+        }
+    }
+
+    #[test]
+    fn suite_shallow() {
+        check_suite(100_000);
+    }
+
+    #[test]
+    #[ignore]
+    fn suite_deep() {
+        check_suite(u64::MAX);
+    }
+
+    #[test]
+    fn divide_adds_up_to_perft() {
+        let chess_board = ChessBoard::from_fen(PERFT_SUITE[1].fen).unwrap();
+        let total: u64 = chess_board.divide(2).iter().map(|&(_, nodes)| nodes).sum();
+        assert_eq!(total, chess_board.perft(2));
+        assert!(chess_board.divide(0).is_empty());
+        assert_eq!(chess_board.perft(0), 1);
+    }
+}
@@ -0,0 +1,545 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{resolve_move, ChessBoard, ChessMove, FenError, MoveError, Operation, Player, SanError, STARTING_FEN};
+
+const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+/// Export format keeps movetext lines shorter than 80 characters.
+const LINE_WIDTH: usize = 79;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PgnError {
+    InvalidTag { line: usize },
+    /// A `}` or `]` with no `{` or `[` before it.
+    UnmatchedBracket { line: usize, bracket: char },
+    UnterminatedComment { line: usize },
+    UnbalancedVariation { line: usize },
+    InvalidFen { line: usize, error: FenError },
+    InvalidMove { line: usize, error: SanError },
+    /// The movetext stopped without a result token such as `1-0` or `*`.
+    MissingResult { line: usize },
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PgnError::InvalidTag { line } => write!(f, "line {}: malformed tag pair", line),
+            PgnError::UnmatchedBracket { line, bracket } => write!(f, "line {}: '{}' was never opened", line, bracket),
+            PgnError::UnterminatedComment { line } => write!(f, "line {}: comment is never closed", line),
+            PgnError::UnbalancedVariation { line } => write!(f, "line {}: unbalanced parenthesis", line),
+            PgnError::InvalidFen { line, error } => write!(f, "line {}: invalid FEN tag, {}", line, error),
+            PgnError::InvalidMove { line, error } => write!(f, "line {}: {}", line, error),
+            PgnError::MissingResult { line } => write!(f, "line {}: game has no result", line),
+        }
+    }
+}
+
+impl Error for PgnError {}
+
+/// A move in a game together with the annotations that followed it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PgnMove {
+    pub chess_move: ChessMove,
+    pub nags: Vec<u8>,
+    pub comment: Option<String>,
+}
+
+/// A game as read from or written to Portable Game Notation. Only the main line is kept, so
+/// variations are not part of a game.
+#[derive(Clone)]
+pub struct PgnGame {
+    tags: Vec<(String, String)>,
+    start: ChessBoard,
+    /// The position after the last move.
+    end: ChessBoard,
+    /// A comment about the whole game, written before the first move.
+    comment: Option<String>,
+    moves: Vec<PgnMove>,
+}
+
+impl PgnGame {
+    /// A game without moves from `start`, with the seven tag roster filled with placeholders.
+    pub fn new(start: ChessBoard) -> Self {
+        let mut game = PgnGame {
+            tags: Vec::new(),
+            end: start.clone(),
+            start,
+            comment: None,
+            moves: Vec::new(),
+        };
+        for &name in &SEVEN_TAG_ROSTER {
+            game.set_tag(name, if name == "Result" { "*" } else { "?" });
+        }
+        let fen = game.start.to_fen();
+        if fen != STARTING_FEN {
+            game.set_tag("SetUp", "1");
+            game.set_tag("FEN", &fen);
+        }
+        game
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn result(&self) -> &str {
+        self.tag("Result").unwrap_or("*")
+    }
+
+    pub fn start(&self) -> &ChessBoard {
+        &self.start
+    }
+
+    /// The position after the last move.
+    pub fn position(&self) -> &ChessBoard {
+        &self.end
+    }
+
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn set_comment(&mut self, comment: &str) {
+        self.comment = Some(comment.to_string());
+    }
+
+    pub fn moves(&self) -> &[PgnMove] {
+        &self.moves
+    }
+
+    /// Appends a move, unless it is not legal in the position reached so far.
+    pub fn push(&mut self, chess_move: ChessMove) -> Result<(), MoveError> {
+        let chess_move = resolve_move(&Operation::from(chess_move), &self.end)?;
+        self.push_legal(chess_move);
+        Ok(())
+    }
+
+    fn push_legal(&mut self, chess_move: ChessMove) {
+        self.end.make_move(chess_move);
+        self.moves.push(PgnMove {
+            chess_move,
+            nags: Vec::new(),
+            comment: None,
+        });
+    }
+
+    /// The starting position followed by the position after every move.
+    pub fn replay(&self) -> Vec<ChessBoard> {
+        let mut boards = vec![self.start.clone()];
+        for pgn_move in &self.moves {
+            let mut next = boards[boards.len() - 1].clone();
+            next.make_move(pgn_move.chess_move);
+            boards.push(next);
+        }
+        boards
+    }
+
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        let roster = SEVEN_TAG_ROSTER.iter().filter_map(|&name| self.tags.iter().find(|(tag, _)| tag == name));
+        let others = self.tags.iter().filter(|(tag, _)| !SEVEN_TAG_ROSTER.contains(&tag.as_str()));
+        for (name, value) in roster.chain(others) {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            pgn.push_str(&format!("[{} \"{}\"]\n", name, value));
+        }
+        pgn.push('\n');
+
+        let mut tokens = Vec::new();
+        if let Some(comment) = &self.comment {
+            tokens.push(format!("{{{}}}", comment));
+        }
+        let mut board = self.start.clone();
+        let mut needs_number = true;
+        for pgn_move in &self.moves {
+            match board.turn {
+                Player::White => tokens.push(format!("{}.", board.fullmove_number)),
+                Player::Black if needs_number => tokens.push(format!("{}...", board.fullmove_number)),
+                Player::Black => (),
+            }
+            tokens.push(board.to_san(pgn_move.chess_move));
+            for nag in &pgn_move.nags {
+                tokens.push(format!("${}", nag));
+            }
+            needs_number = false;
+            if let Some(comment) = &pgn_move.comment {
+                tokens.push(format!("{{{}}}", comment));
+                needs_number = true;
+            }
+            board.make_move(pgn_move.chess_move);
+        }
+        tokens.push(self.result().to_string());
+
+        let mut line_length = 0;
+        for token in tokens {
+            if line_length > 0 && line_length + 1 + token.len() > LINE_WIDTH {
+                pgn.push('\n');
+                line_length = 0;
+            } else if line_length > 0 {
+                pgn.push(' ');
+                line_length += 1;
+            }
+            line_length += token.len();
+            pgn.push_str(&token);
+        }
+        pgn.push('\n');
+        pgn
+    }
+}
+
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    VariationStart,
+    VariationEnd,
+    Result(String),
+    San(String),
+}
+
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, PgnError> {
+    let lines = text
+        .lines()
+        .enumerate()
+        .map(|(line_idx, content)| (line_idx + 1, content))
+        .filter(|(_, content)| !content.starts_with('%'));
+
+    let mut result = Vec::new();
+    let mut open_comment: Option<(usize, String)> = None;
+    let mut open_variations = Vec::new();
+    for (line, content) in lines {
+        let mut rest = content;
+        if let Some((start, mut comment)) = open_comment.take() {
+            match rest.find('}') {
+                Some(end) => {
+                    comment.push(' ');
+                    comment.push_str(&rest[..end]);
+                    result.push((start, Token::Comment(comment.trim().to_string())));
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    comment.push(' ');
+                    comment.push_str(rest);
+                    open_comment = Some((start, comment));
+                    continue;
+                }
+            }
+        }
+
+        while let Some(c) = rest.chars().next() {
+            if c.is_whitespace() {
+                rest = &rest[c.len_utf8()..];
+            } else if c == ';' {
+                break;
+            } else if c == '{' {
+                match rest.find('}') {
+                    Some(end) => {
+                        result.push((line, Token::Comment(rest[1..end].trim().to_string())));
+                        rest = &rest[end + 1..];
+                    }
+                    None => {
+                        open_comment = Some((line, rest[1..].to_string()));
+                        break;
+                    }
+                }
+            } else if c == '[' {
+                let end = tag_end(rest).ok_or(PgnError::InvalidTag { line })?;
+                let (name, value) = parse_tag(&rest[1..end]).ok_or(PgnError::InvalidTag { line })?;
+                result.push((line, Token::Tag(name, value)));
+                rest = &rest[end + 1..];
+            } else if c == '(' {
+                open_variations.push(line);
+                result.push((line, Token::VariationStart));
+                rest = &rest[1..];
+            } else if c == ')' {
+                if open_variations.pop().is_none() {
+                    return Err(PgnError::UnbalancedVariation { line });
+                }
+                result.push((line, Token::VariationEnd));
+                rest = &rest[1..];
+            } else if c == '}' || c == ']' {
+                return Err(PgnError::UnmatchedBracket { line, bracket: c });
+            } else {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || "{}()[];".contains(c))
+                    .unwrap_or(rest.len());
+                let word = &rest[..end];
+                rest = &rest[end..];
+                push_word(line, word, &mut result);
+            }
+        }
+    }
+    if let Some((line, _)) = open_comment {
+        return Err(PgnError::UnterminatedComment { line });
+    }
+    if let Some(line) = open_variations.pop() {
+        return Err(PgnError::UnbalancedVariation { line });
+    }
+    Ok(result)
+}
+
+/// Splits a symbol into its move number, move, suffix annotation or result.
+fn push_word(line: usize, word: &str, tokens: &mut Vec<(usize, Token)>) {
+    if RESULTS.contains(&word) {
+        tokens.push((line, Token::Result(word.to_string())));
+        return;
+    }
+    if let Some(nag) = word.strip_prefix('$') {
+        if let Ok(nag) = nag.parse() {
+            tokens.push((line, Token::Nag(nag)));
+        }
+        return;
+    }
+    // A move number is digits followed by dots, so castling written with zeros is left alone.
+    let unnumbered = word.trim_start_matches(|c: char| c.is_ascii_digit());
+    let word = match unnumbered.strip_prefix('.') {
+        Some(rest) if unnumbered.len() < word.len() => rest.trim_start_matches('.'),
+        _ => word,
+    };
+    if word.is_empty() {
+        return;
+    }
+    let san = word.trim_end_matches(['!', '?']);
+    let nag = match &word[san.len()..] {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    };
+    tokens.push((line, Token::San(san.to_string())));
+    if let Some(nag) = nag {
+        tokens.push((line, Token::Nag(nag)));
+    }
+}
+
+/// The index of the `]` closing the tag pair at the start of `text`, looking past any `]` inside
+/// the quoted value.
+fn tag_end(text: &str) -> Option<usize> {
+    let mut in_value = false;
+    let mut escaped = false;
+    for (idx, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_value => escaped = true,
+            '"' => in_value = !in_value,
+            ']' if !in_value => return Some(idx),
+            _ => (),
+        }
+    }
+    None
+}
+
+fn parse_tag(content: &str) -> Option<(String, String)> {
+    let content = content.trim();
+    let name_end = content.find(char::is_whitespace)?;
+    let name = &content[..name_end];
+    let quoted = content[name_end..].trim();
+    let quoted = quoted.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            value.push(chars.next()?);
+        } else {
+            value.push(c);
+        }
+    }
+    Some((name.to_string(), value))
+}
+
+fn start_game(tags: &[(usize, String, String)]) -> Result<PgnGame, PgnError> {
+    let start = match tags.iter().find(|(_, name, _)| name == "FEN") {
+        Some((line, _, fen)) => {
+            ChessBoard::from_fen(fen).map_err(|error| PgnError::InvalidFen { line: *line, error })?
+        }
+        None => ChessBoard::new(),
+    };
+    let mut game = PgnGame::new(start);
+    for (_, name, value) in tags {
+        game.set_tag(name, value);
+    }
+    Ok(game)
+}
+
+/// Reads every game in a PGN file. Variations are skipped and only the main line is kept.
+pub fn read_pgn(text: &str) -> Result<Vec<PgnGame>, PgnError> {
+    let mut games = Vec::new();
+    let mut tags: Vec<(usize, String, String)> = Vec::new();
+    let mut current: Option<PgnGame> = None;
+    let mut depth = 0;
+    let mut last_line = 0;
+
+    for (line, token) in tokenize(text)? {
+        last_line = line;
+        if let Token::Tag(name, value) = token {
+            if current.is_some() {
+                return Err(PgnError::MissingResult { line });
+            }
+            tags.push((line, name, value));
+            continue;
+        }
+
+        if current.is_none() {
+            current = Some(start_game(&tags)?);
+            tags.clear();
+        }
+        let game = current.as_mut().unwrap();
+
+        match token {
+            Token::VariationStart => depth += 1,
+            Token::VariationEnd => depth -= 1,
+            _ if depth > 0 => (),
+            Token::San(san) => {
+                let chess_move = game
+                    .end
+                    .parse_san(&san)
+                    .map_err(|error| PgnError::InvalidMove { line, error })?;
+                game.push_legal(chess_move);
+            }
+            Token::Nag(nag) => {
+                if let Some(pgn_move) = game.moves.last_mut() {
+                    pgn_move.nags.push(nag);
+                }
+            }
+            Token::Comment(comment) => {
+                let target = match game.moves.last_mut() {
+                    Some(pgn_move) => &mut pgn_move.comment,
+                    None => &mut game.comment,
+                };
+                // Comments in a row are kept together, so that writing the game back loses nothing.
+                match target {
+                    Some(previous) => {
+                        previous.push(' ');
+                        previous.push_str(&comment);
+                    }
+                    None => *target = Some(comment),
+                }
+            }
+            Token::Result(result) => {
+                game.set_tag("Result", &result);
+                games.push(game.clone());
+                current = None;
+            }
+            Token::Tag(_, _) => unreachable!(),
+        }
+    }
+
+    if current.is_some() || !tags.is_empty() {
+        return Err(PgnError::MissingResult { line: last_line });
+    }
+    Ok(games)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn movetext(game: &PgnGame) -> String {
+        let pgn = game.to_pgn();
+        pgn[pgn.find("\n\n").unwrap() + 2..].to_string()
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = "[Event \"Club \\\"open\\\"\"]\n[Site \"?\"]\n[Date \"?\"]\n[Round \"?\"]\n[White \"?\"]\n\
+                    [Black \"?\"]\n[Result \"1-0\"]\n\n{Notes} 1. e4 $1 e5 {first} 2. Nf3 Nc6 3. Bb5 1-0\n";
+        let games = read_pgn(text).unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].tag("Event"), Some("Club \"open\""));
+        assert_eq!(games[0].to_pgn(), text);
+    }
+
+    #[test]
+    fn reads_move_numbers_castling_and_annotations() {
+        let games = read_pgn("1.e4 e5 2.Nf3 Nc6 3.Bc4 Bc5 4.0-0!? 4...Nf6 *").unwrap();
+        let moves = games[0].moves();
+        assert_eq!(moves.len(), 8);
+        assert_eq!(moves[6].chess_move, ChessMove::new(4, 6));
+        assert_eq!(moves[6].nags, vec![5]);
+    }
+
+    #[test]
+    fn skips_variations() {
+        let games = read_pgn("1. e4 (1. d4 d5) 1... e5 {main} (1... c5 2. Nf3) 2. Nf3 *").unwrap();
+        assert_eq!(movetext(&games[0]), "1. e4 e5 {main} 2. Nf3 *\n");
+    }
+
+    #[test]
+    fn keeps_every_comment() {
+        let games = read_pgn("1. e4 {first} {second} *").unwrap();
+        assert_eq!(games[0].moves()[0].comment.as_deref(), Some("first second"));
+    }
+
+    #[test]
+    fn tag_value_may_contain_brackets() {
+        let games = read_pgn("[Event \"Blitz [5+0]\"]\n\n1. e4 *").unwrap();
+        assert_eq!(games[0].tag("Event"), Some("Blitz [5+0]"));
+    }
+
+    #[test]
+    fn rejects_unmatched_brackets() {
+        assert_eq!(read_pgn("}").err(), Some(PgnError::UnmatchedBracket { line: 1, bracket: '}' }));
+        assert_eq!(read_pgn("]").err(), Some(PgnError::UnmatchedBracket { line: 1, bracket: ']' }));
+        assert_eq!(
+            read_pgn("1. e4 e5 } *").err(),
+            Some(PgnError::UnmatchedBracket { line: 1, bracket: '}' })
+        );
+        assert_eq!(
+            read_pgn("[Event \"x\"]]").err(),
+            Some(PgnError::UnmatchedBracket { line: 1, bracket: ']' })
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_games() {
+        assert_eq!(read_pgn("[Event \"x\"").err(), Some(PgnError::InvalidTag { line: 1 }));
+        assert_eq!(read_pgn("1. e4 {open\n*").err(), Some(PgnError::UnterminatedComment { line: 1 }));
+        assert_eq!(read_pgn("1. e4 (1. d4 *").err(), Some(PgnError::UnbalancedVariation { line: 1 }));
+        assert_eq!(read_pgn("1. e4 e5").err(), Some(PgnError::MissingResult { line: 1 }));
+        assert_eq!(
+            read_pgn("1. e5 *").err(),
+            Some(PgnError::InvalidMove {
+                line: 1,
+                error: SanError::Illegal("e5".to_string())
+            })
+        );
+    }
+
+    #[test]
+    fn push_rejects_illegal_moves() {
+        let mut game = PgnGame::new(ChessBoard::new());
+        assert_eq!(game.push(ChessMove::new(20, 28)), Err(MoveError::EmptySquare(20)));
+        assert_eq!(game.push(ChessMove::new(12, 28)), Ok(()));
+        assert_eq!(game.push(ChessMove::new(12, 20)), Err(MoveError::EmptySquare(12)));
+        assert_eq!(game.moves().len(), 1);
+        assert_eq!(movetext(&game), "1. e4 *\n");
+    }
+
+    #[test]
+    fn movetext_lines_stay_under_80_characters() {
+        // Comments of every length shift where the lines break.
+        for length in 1..70 {
+            let mut game = PgnGame::new(ChessBoard::new());
+            game.set_comment(&"x".repeat(length));
+            for _ in 0..10 {
+                for &(from, to) in &[(6, 21), (62, 45), (21, 6), (45, 62)] {
+                    game.push(ChessMove::new(from, to)).unwrap();
+                }
+            }
+            assert!(game.to_pgn().lines().all(|line| line.len() < 80), "comment of {} characters", length);
+        }
+    }
+}
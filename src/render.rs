@@ -0,0 +1,142 @@
+use std::fmt;
+
+use crate::{square_name, to_idx, ChessBoard, ChessMove, ChessPiece, Piece, Player};
+
+const LAST_MOVE: &str = "\x1b[30;43m";
+const CHECK: &str = "\x1b[97;41m";
+const RESET: &str = "\x1b[0m";
+
+/// How pieces are drawn: FEN letters or the Unicode chess symbols.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Glyphs {
+    Ascii,
+    Unicode,
+}
+
+impl ChessPiece {
+    pub fn glyph(&self) -> char {
+        match (self.player, self.piece) {
+            (Player::White, Piece::King) => '♔',
+            (Player::White, Piece::Queen) => '♕',
+            (Player::White, Piece::Rook) => '♖',
+            (Player::White, Piece::Bishop) => '♗',
+            (Player::White, Piece::Knight) => '♘',
+            (Player::White, Piece::Pawn) => '♙',
+            (Player::Black, Piece::King) => '♚',
+            (Player::Black, Piece::Queen) => '♛',
+            (Player::Black, Piece::Rook) => '♜',
+            (Player::Black, Piece::Bishop) => '♝',
+            (Player::Black, Piece::Knight) => '♞',
+            (Player::Black, Piece::Pawn) => '♟',
+        }
+    }
+}
+
+/// A text diagram of a board with rank and file labels. Built with `ChessBoard::view`, without
+/// colour unless asked for.
+#[derive(Debug, Copy, Clone)]
+pub struct BoardView<'a> {
+    chess_board: &'a ChessBoard,
+    glyphs: Glyphs,
+    orientation: Player,
+    last_move: Option<ChessMove>,
+    colour: bool,
+}
+
+impl<'a> BoardView<'a> {
+    pub fn glyphs(mut self, glyphs: Glyphs) -> Self {
+        self.glyphs = glyphs;
+        self
+    }
+
+    /// The side whose pieces are drawn at the bottom.
+    pub fn orientation(mut self, player: Player) -> Self {
+        self.orientation = player;
+        self
+    }
+
+    /// Highlights the squares the move went from and to.
+    pub fn last_move(mut self, chess_move: Option<ChessMove>) -> Self {
+        self.last_move = chess_move;
+        self
+    }
+
+    /// Whether to use ANSI colours to highlight the last move and a king in check.
+    pub fn colour(mut self, colour: bool) -> Self {
+        self.colour = colour;
+        self
+    }
+
+    fn highlight(&self, square: usize) -> Option<&'static str> {
+        if !self.colour {
+            return None;
+        }
+        let turn = self.chess_board.turn;
+        if self.chess_board.king_square(turn) == Some(square) && self.chess_board.in_check(turn) {
+            Some(CHECK)
+        } else if self.last_move.is_some_and(|chess_move| chess_move.from == square || chess_move.to == square) {
+            Some(LAST_MOVE)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for BoardView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (rows, cols): (Vec<usize>, Vec<usize>) = match self.orientation {
+            Player::White => ((0..8).rev().collect(), (0..8).collect()),
+            Player::Black => ((0..8).collect(), (0..8).rev().collect()),
+        };
+        for &row in &rows {
+            write!(f, "{} ", row + 1)?;
+            for &col in &cols {
+                let square = to_idx(row, col);
+                let symbol = match (self.chess_board.board[square].value, self.glyphs) {
+                    (Some(chess_piece), Glyphs::Ascii) => chess_piece.letter(),
+                    (Some(chess_piece), Glyphs::Unicode) => chess_piece.glyph(),
+                    (None, Glyphs::Ascii) => '.',
+                    (None, Glyphs::Unicode) => '·',
+                };
+                match self.highlight(square) {
+                    Some(colour) => write!(f, " {}{}{}", colour, symbol, RESET)?,
+                    None => write!(f, " {}", symbol)?,
+                }
+            }
+            writeln!(f)?;
+        }
+        write!(f, "  ")?;
+        for &col in &cols {
+            write!(f, " {}", &square_name(col)[..1])?;
+        }
+        writeln!(f)
+    }
+}
+
+impl ChessBoard {
+    /// A diagram of the board from White's side in ASCII, to be adjusted with the `BoardView`
+    /// setters.
+    pub fn view(&self) -> BoardView<'_> {
+        BoardView {
+            chess_board: self,
+            glyphs: Glyphs::Ascii,
+            orientation: Player::White,
+            last_move: None,
+            colour: false,
+        }
+    }
+}
+
+/// Draws the board from White's side; the alternate form `{:#}` uses Unicode glyphs.
+impl fmt::Display for ChessBoard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let glyphs = if f.alternate() { Glyphs::Unicode } else { Glyphs::Ascii };
+        write!(f, "{}", self.view().glyphs(glyphs))
+    }
+}
+
+impl fmt::Debug for ChessBoard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ChessBoard").field(&self.to_fen()).finish()
+    }
+}
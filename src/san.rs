@@ -0,0 +1,243 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{get_col, get_row, parse_square, square_name, ChessBoard, ChessMove, Piece};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SanError {
+    /// The text is not shaped like a SAN move.
+    Invalid(String),
+    /// No legal move in the position matches.
+    Illegal(String),
+    /// More than one legal move matches, so a file or rank is needed to tell them apart.
+    Ambiguous(String),
+    /// A pawn reaching the last rank was not given a piece to promote to.
+    MissingPromotion(String),
+}
+
+impl fmt::Display for SanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SanError::Invalid(san) => write!(f, "'{}' is not a move in algebraic notation", san),
+            SanError::Illegal(san) => write!(f, "{} is not legal in this position", san),
+            SanError::Ambiguous(san) => write!(f, "{} is ambiguous, add the file or rank of the piece", san),
+            SanError::MissingPromotion(san) => write!(f, "{} needs a piece to promote to, e.g. e8=Q", san),
+        }
+    }
+}
+
+impl Error for SanError {}
+
+impl ChessBoard {
+    /// Finds the legal move written in Standard Algebraic Notation, such as `Nf3`, `exd5`,
+    /// `O-O-O` or `e8=Q+`. Check, mate and annotation suffixes are ignored.
+    pub fn parse_san(&self, san: &str) -> Result<ChessMove, SanError> {
+        let chess_move = self.find_san(san)?;
+        if chess_move.promotion.is_none() && !self.legal_moves().contains(&chess_move) {
+            return Err(SanError::MissingPromotion(san.to_string()));
+        }
+        Ok(chess_move)
+    }
+
+    /// Like `parse_san`, except that a pawn move to the last rank written without a piece is
+    /// returned without one, for `resolve_move` to report.
+    pub(crate) fn find_san(&self, san: &str) -> Result<ChessMove, SanError> {
+        let invalid = || SanError::Invalid(san.to_string());
+        let text = san.trim_end_matches(['+', '#', '!', '?']);
+
+        let castle = match text {
+            "O-O" | "0-0" => Some(2),
+            "O-O-O" | "0-0-0" => Some(-2),
+            _ => None,
+        };
+        if let Some(shift) = castle {
+            let king = self.king_square(self.turn).ok_or_else(invalid)?;
+            let to = (king as i32 + shift) as usize;
+            return self
+                .legal_moves()
+                .into_iter()
+                .find(|chess_move| chess_move.from == king && chess_move.to == to)
+                .ok_or_else(|| SanError::Illegal(san.to_string()));
+        }
+
+        let (text, promotion) = match text.split_once('=') {
+            Some((text, piece)) => {
+                let mut letters = piece.chars();
+                match (letters.next().and_then(Piece::from_letter), letters.next()) {
+                    (Some(piece), None) => (text, Some(piece)),
+                    (_, _) => return Err(invalid()),
+                }
+            }
+            None => match text.chars().last().and_then(Piece::from_letter) {
+                Some(piece) if text.len() > 2 && !text.starts_with(|c: char| c.is_ascii_uppercase()) => {
+                    (&text[..text.len() - 1], Some(piece))
+                }
+                _ => (text, None),
+            },
+        };
+
+        let (piece, text) = match text.chars().next().and_then(Piece::from_letter) {
+            Some(piece) => (piece, &text[1..]),
+            None => (Piece::Pawn, text),
+        };
+        if text.len() < 2 || !text.is_char_boundary(text.len() - 2) {
+            return Err(invalid());
+        }
+        let (qualifier, destination) = text.split_at(text.len() - 2);
+        let to = parse_square(destination).ok_or_else(invalid)?;
+        let qualifier = qualifier.strip_suffix('x').unwrap_or(qualifier);
+
+        let mut from_col = None;
+        let mut from_row = None;
+        for c in qualifier.chars() {
+            match c {
+                'a'..='h' if from_col.is_none() && from_row.is_none() => from_col = Some(c as usize - 'a' as usize),
+                '1'..='8' if from_row.is_none() => from_row = Some(c as usize - '1' as usize),
+                _ => return Err(invalid()),
+            }
+        }
+        if piece == Piece::Pawn && from_col.is_none() {
+            from_col = Some(get_col(to));
+        }
+
+        let mut candidates: Vec<ChessMove> = self
+            .legal_moves()
+            .into_iter()
+            .filter(|chess_move| {
+                chess_move.to == to
+                    && (promotion.is_none() || chess_move.promotion == promotion)
+                    && self.board[chess_move.from].value.map(|chess_piece| chess_piece.piece) == Some(piece)
+                    && from_col.is_none_or(|col| get_col(chess_move.from) == col)
+                    && from_row.is_none_or(|row| get_row(chess_move.from) == row)
+            })
+            .map(|chess_move| ChessMove { promotion, ..chess_move })
+            .collect();
+        // The four promotions of one pawn are generated together.
+        candidates.dedup();
+        match candidates.as_slice() {
+            [chess_move] => Ok(*chess_move),
+            [] => Err(SanError::Illegal(san.to_string())),
+            _ => Err(SanError::Ambiguous(san.to_string())),
+        }
+    }
+
+    /// Writes a legal move in Standard Algebraic Notation, including the check or mate suffix.
+    pub fn to_san(&self, chess_move: ChessMove) -> String {
+        let chess_piece = match self.board[chess_move.from].value {
+            Some(chess_piece) => chess_piece,
+            None => return square_name(chess_move.from) + &square_name(chess_move.to),
+        };
+        let mut san = String::new();
+
+        if chess_piece.piece == Piece::King && chess_move.to == chess_move.from + 2 {
+            san.push_str("O-O");
+        } else if chess_piece.piece == Piece::King && chess_move.to + 2 == chess_move.from {
+            san.push_str("O-O-O");
+        } else if chess_piece.piece == Piece::Pawn {
+            if get_col(chess_move.from) != get_col(chess_move.to) {
+                san.push((b'a' + get_col(chess_move.from) as u8) as char);
+                san.push('x');
+            }
+            san.push_str(&square_name(chess_move.to));
+            if let Some(promotion) = chess_move.promotion {
+                san.push('=');
+                san.push(promotion.letter());
+            }
+        } else {
+            san.push(chess_piece.piece.letter());
+            let rivals: Vec<ChessMove> = self
+                .legal_moves()
+                .into_iter()
+                .filter(|other| {
+                    other.to == chess_move.to
+                        && other.from != chess_move.from
+                        && self.board[other.from].value.map(|other_piece| other_piece.piece) == Some(chess_piece.piece)
+                })
+                .collect();
+            if !rivals.is_empty() {
+                let name = square_name(chess_move.from);
+                if rivals.iter().all(|other| get_col(other.from) != get_col(chess_move.from)) {
+                    san.push_str(&name[..1]);
+                } else if rivals.iter().all(|other| get_row(other.from) != get_row(chess_move.from)) {
+                    san.push_str(&name[1..]);
+                } else {
+                    san.push_str(&name);
+                }
+            }
+            if self.board[chess_move.to].value.is_some() {
+                san.push('x');
+            }
+            san.push_str(&square_name(chess_move.to));
+        }
+
+        let mut next = self.clone();
+        next.make_move(chess_move);
+        if next.in_check(next.turn) {
+            san.push(if next.legal_moves().is_empty() { '#' } else { '+' });
+        }
+        san
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(fen: &str) -> ChessBoard {
+        ChessBoard::from_fen(fen).unwrap()
+    }
+
+    fn promotion(from: usize, to: usize, piece: Piece) -> ChessMove {
+        ChessMove {
+            promotion: Some(piece),
+            ..ChessMove::new(from, to)
+        }
+    }
+
+    #[test]
+    fn every_legal_move_round_trips() {
+        for fen in &[
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "1k6/8/8/8/7Q/8/8/K3Q2Q w - - 0 1",
+        ] {
+            let chess_board = board(fen);
+            for chess_move in chess_board.legal_moves() {
+                assert_eq!(chess_board.parse_san(&chess_board.to_san(chess_move)), Ok(chess_move));
+            }
+        }
+    }
+
+    #[test]
+    fn disambiguates_by_file_rank_or_square() {
+        let chess_board = board("1k6/8/8/8/7Q/8/8/K3Q2Q w - - 0 1");
+        assert_eq!(chess_board.to_san(ChessMove::new(4, 28)), "Qee4");
+        assert_eq!(chess_board.to_san(ChessMove::new(31, 28)), "Q4e4");
+        assert_eq!(chess_board.to_san(ChessMove::new(7, 28)), "Qh1e4");
+        assert_eq!(chess_board.parse_san("Qe4"), Err(SanError::Ambiguous("Qe4".to_string())));
+        assert_eq!(chess_board.parse_san("Qhe4"), Err(SanError::Ambiguous("Qhe4".to_string())));
+    }
+
+    #[test]
+    fn writes_suffixes_castling_and_promotion() {
+        let chess_board = board("r3k3/1P6/8/8/8/8/8/4K2R w Kq - 0 1");
+        assert_eq!(chess_board.to_san(ChessMove::new(4, 6)), "O-O");
+        assert_eq!(chess_board.to_san(ChessMove::new(7, 63)), "Rh8+");
+        assert_eq!(chess_board.to_san(promotion(49, 56, Piece::Queen)), "bxa8=Q+");
+        assert_eq!(chess_board.parse_san("0-0"), Ok(ChessMove::new(4, 6)));
+        assert_eq!(chess_board.parse_san("bxa8=N+"), Ok(promotion(49, 56, Piece::Knight)));
+        assert_eq!(board("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").to_san(ChessMove::new(0, 56)), "Ra8#");
+    }
+
+    #[test]
+    fn rejects_bad_moves() {
+        let chess_board = ChessBoard::new();
+        assert_eq!(chess_board.parse_san("e5"), Err(SanError::Illegal("e5".to_string())));
+        assert_eq!(chess_board.parse_san("O-O"), Err(SanError::Illegal("O-O".to_string())));
+        assert_eq!(chess_board.parse_san("Zf3"), Err(SanError::Invalid("Zf3".to_string())));
+        assert_eq!(chess_board.parse_san("e"), Err(SanError::Invalid("e".to_string())));
+        let promoting = board("7k/P7/8/8/8/8/8/7K w - - 0 1");
+        assert_eq!(promoting.parse_san("a8"), Err(SanError::MissingPromotion("a8".to_string())));
+        assert_eq!(promoting.parse_san("a8=K"), Err(SanError::Illegal("a8=K".to_string())));
+    }
+}
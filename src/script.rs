@@ -0,0 +1,41 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{play_command, ChessBoard, CommandError};
+
+/// The first command in a game script that could not be played.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ScriptError {
+    /// Line number in the script, starting at 1.
+    pub line: usize,
+    pub command: String,
+    pub error: CommandError,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {} `{}`: {}", self.line, self.command, self.error)
+    }
+}
+
+impl Error for ScriptError {}
+
+/// Plays every command of a game script in order, one command per line, starting from `chess_board`.
+/// Blank lines and lines starting with `#` are skipped. Stops at the first command that fails,
+/// leaving the board in the position before it.
+pub fn run_script(script: &str, chess_board: &mut ChessBoard) -> Result<(), ScriptError> {
+    for (line_idx, line) in script.lines().enumerate() {
+        let command = line.trim();
+        if command.is_empty() || command.starts_with('#') {
+            continue;
+        }
+        if let Err(error) = play_command(command, chess_board) {
+            return Err(ScriptError {
+                line: line_idx + 1,
+                command: command.to_string(),
+                error,
+            });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,306 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::eval::evaluate;
+use crate::ordering::{order_captures, MoveOrdering};
+use crate::transposition::{Bound, Entry, TranspositionTable};
+use crate::{ChessBoard, ChessMove};
+
+/// The score of delivering mate right now. Mates further away score one less per ply.
+pub const MATE: i32 = 32_000;
+/// Scores beyond this are mates rather than material.
+pub(crate) const MATE_BOUND: i32 = MATE - 1_000;
+const INFINITY: i32 = MATE + 1;
+const MAX_DEPTH: u32 = 64;
+/// The quiescence search stops following captures this many plies from the root.
+const MAX_PLY: usize = 128;
+/// How many nodes pass between looks at the stop flag.
+const STOP_CHECK_INTERVAL: u64 = 1024;
+
+/// When to stop searching. Without any limit the search runs to its maximum depth.
+#[derive(Debug, Clone, Default)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    /// Set from another thread to stop the search; the last completed iteration is returned.
+    pub stop: Option<Arc<AtomicBool>>,
+}
+
+/// The outcome of the deepest completed iteration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    /// `None` only when the side to move has no legal move.
+    pub best_move: Option<ChessMove>,
+    /// Centipawns from the point of view of the side to move, or a mate score near `MATE`.
+    pub score: i32,
+    pub depth: u32,
+    pub nodes: u64,
+    /// The principal variation: the line both sides are expected to play, starting with `best_move`.
+    pub pv: Vec<ChessMove>,
+}
+
+impl SearchResult {
+    /// The number of moves until mate if the score is a mate score, negative when the side to move
+    /// is the one getting mated.
+    pub fn mate_in(&self) -> Option<i32> {
+        if self.score > MATE_BOUND {
+            Some((MATE - self.score + 1) / 2)
+        } else if self.score < -MATE_BOUND {
+            Some(-(MATE + self.score) / 2)
+        } else {
+            None
+        }
+    }
+}
+
+/// Iterative deepening negamax search with alpha-beta pruning.
+#[derive(Debug, Default)]
+pub struct Searcher {
+    limits: SearchLimits,
+    nodes: u64,
+    stopped: bool,
+    /// The principal variation of the previous iteration, searched first in the next one.
+    pv: Vec<ChessMove>,
+    /// Whether every move made so far in this iteration follows `pv`.
+    follow_pv: bool,
+    ordering: MoveOrdering,
+    /// Kept from one search to the next, so that a game searched move after move reuses its work.
+    table: TranspositionTable,
+}
+
+impl Searcher {
+    /// A searcher with a transposition table of `DEFAULT_HASH_MB`.
+    pub fn new() -> Self {
+        Searcher::default()
+    }
+
+    /// A searcher with a transposition table of at most `megabytes`.
+    pub fn with_hash_size(megabytes: usize) -> Self {
+        Searcher {
+            table: TranspositionTable::new(megabytes),
+            ..Searcher::default()
+        }
+    }
+
+    /// Forgets the positions searched so far, for example when a new game starts.
+    pub fn clear_hash(&mut self) {
+        self.table.clear();
+    }
+
+    /// Searches one ply deeper at a time until a limit is hit, calling `report` after every
+    /// completed iteration.
+    pub fn search<F: FnMut(&SearchResult)>(
+        &mut self,
+        chess_board: &ChessBoard,
+        limits: &SearchLimits,
+        mut report: F,
+    ) -> SearchResult {
+        self.limits = limits.clone();
+        self.nodes = 0;
+        self.stopped = false;
+        self.pv.clear();
+        self.ordering = MoveOrdering::default();
+        self.table.new_search();
+
+        let mut scratch = chess_board.clone();
+        let mut result = SearchResult {
+            best_move: chess_board.legal_moves().first().copied(),
+            score: 0,
+            depth: 0,
+            nodes: 0,
+            pv: Vec::new(),
+        };
+        let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+        for depth in 1..=max_depth {
+            let mut pv = Vec::new();
+            self.follow_pv = true;
+            let score = self.negamax(&mut scratch, depth, 0, -INFINITY, INFINITY, &mut pv);
+            if self.stopped {
+                break;
+            }
+            result = SearchResult {
+                best_move: pv.first().copied(),
+                score,
+                depth,
+                nodes: self.nodes,
+                pv: pv.clone(),
+            };
+            report(&result);
+            self.pv = pv;
+            // Every line has been searched up to this depth, so a mate found now cannot get shorter.
+            if result.best_move.is_none() || result.mate_in().is_some() {
+                break;
+            }
+        }
+        result.nodes = self.nodes;
+        result
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes) {
+            self.stopped = true;
+        } else if self.nodes.is_multiple_of(STOP_CHECK_INTERVAL) {
+            if let Some(stop) = &self.limits.stop {
+                self.stopped = stop.load(Ordering::Relaxed);
+            }
+        }
+        self.stopped
+    }
+
+    /// The score of the position for the side to move, searched `depth` plies deep, with the
+    /// best line found written to `pv`.
+    fn negamax(
+        &mut self,
+        chess_board: &mut ChessBoard,
+        depth: u32,
+        ply: usize,
+        mut alpha: i32,
+        beta: i32,
+        pv: &mut Vec<ChessMove>,
+    ) -> i32 {
+        pv.clear();
+        if self.should_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+
+        if ply > 0
+            && (chess_board.halfmove_clock >= 100
+                || chess_board.repetitions() >= 2
+                || chess_board.is_insufficient_material())
+        {
+            return 0;
+        }
+
+        if depth == 0 {
+            return self.quiescence(chess_board, ply, alpha, beta);
+        }
+
+        let entry = self.table.probe(chess_board.hash, ply);
+        // The root always searches, so that there is a best move and a principal variation.
+        if let Some(entry) = entry.filter(|entry| ply > 0 && entry.depth >= depth) {
+            match entry.bound {
+                Bound::Exact => {
+                    pv.extend(entry.best_move);
+                    return entry.score;
+                }
+                Bound::Lower if entry.score >= beta => return beta,
+                Bound::Upper if entry.score <= alpha => return alpha,
+                _ => (),
+            }
+        }
+
+        let mut moves = chess_board.legal_moves();
+        if moves.is_empty() {
+            return if chess_board.in_check(chess_board.turn) { -MATE + ply as i32 } else { 0 };
+        }
+        // Without a stored move, the move the previous iteration preferred here is tried first, as
+        // long as the moves leading here are the ones it preferred too.
+        let on_pv = self.follow_pv;
+        let pv_move = if on_pv { self.pv.get(ply).copied() } else { None };
+        let hash_move = entry.and_then(|entry| entry.best_move).or(pv_move);
+        self.ordering.order(chess_board, &mut moves, hash_move, ply);
+
+        let original_alpha = alpha;
+        let mut best_move = None;
+
+        let mut line = Vec::new();
+        for chess_move in moves {
+            let quiet = !chess_board.is_capture(chess_move) && chess_move.promotion.is_none();
+            self.follow_pv = on_pv && pv_move == Some(chess_move);
+            let undo = chess_board.make_move(chess_move);
+            let score = -self.negamax(chess_board, depth - 1, ply + 1, -beta, -alpha, &mut line);
+            chess_board.unmake_move(undo);
+            if self.stopped {
+                return 0;
+            }
+            if score > alpha {
+                alpha = score;
+                best_move = Some(chess_move);
+                pv.clear();
+                pv.push(chess_move);
+                pv.extend_from_slice(&line);
+                if alpha >= beta {
+                    if quiet {
+                        self.ordering.record_cutoff(chess_board.turn, chess_move, depth, ply);
+                    }
+                    break;
+                }
+            }
+        }
+
+        let bound = if alpha >= beta {
+            Bound::Lower
+        } else if alpha > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        let entry = Entry {
+            depth,
+            bound,
+            score: alpha,
+            best_move,
+        };
+        self.table.store(chess_board.hash, ply, entry);
+        alpha
+    }
+
+    /// Follows captures and promotions past the horizon until the position is quiet, so that the
+    /// static evaluation is never taken in the middle of an exchange. The side to move may
+    /// "stand pat" on the evaluation instead of capturing, unless it is in check, in which case
+    /// every evasion is searched.
+    fn quiescence(&mut self, chess_board: &mut ChessBoard, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        if self.should_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+
+        if ply >= MAX_PLY {
+            return evaluate(chess_board);
+        }
+        let in_check = chess_board.in_check(chess_board.turn);
+        let moves = if in_check {
+            let mut moves = chess_board.legal_moves();
+            if moves.is_empty() {
+                return -MATE + ply as i32;
+            }
+            self.ordering.order(chess_board, &mut moves, None, ply);
+            moves
+        } else {
+            let stand_pat = evaluate(chess_board);
+            if stand_pat >= beta {
+                return beta;
+            }
+            alpha = alpha.max(stand_pat);
+            let mut moves = chess_board.legal_captures();
+            order_captures(chess_board, &mut moves);
+            moves
+        };
+
+        for chess_move in moves {
+            // A capture that loses material cannot raise the score above standing pat.
+            if !in_check && chess_move.promotion.is_none() && chess_board.static_exchange(chess_move) < 0 {
+                continue;
+            }
+            let undo = chess_board.make_move(chess_move);
+            let score = -self.quiescence(chess_board, ply + 1, -beta, -alpha);
+            chess_board.unmake_move(undo);
+            if self.stopped {
+                return 0;
+            }
+            if score >= beta {
+                return beta;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+}
+
+impl ChessBoard {
+    /// Searches the position with a fresh `Searcher`.
+    pub fn search(&self, limits: &SearchLimits) -> SearchResult {
+        Searcher::new().search(self, limits, |_| ())
+    }
+}
@@ -0,0 +1,209 @@
+use std::fmt;
+
+use crate::{get_col, get_row, ChessBoard, Piece, Player};
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum DrawReason {
+    /// Fifty moves by each side without a capture or pawn move; a player may claim the draw.
+    FiftyMoves,
+    /// Seventy-five moves by each side without a capture or pawn move ends the game.
+    SeventyFiveMoves,
+    /// The same position occurred three times; a player may claim the draw.
+    ThreefoldRepetition,
+    /// The same position occurred five times, which ends the game.
+    FivefoldRepetition,
+    /// Neither side has enough material left to checkmate.
+    InsufficientMaterial,
+}
+
+impl fmt::Display for DrawReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DrawReason::FiftyMoves => write!(f, "fifty-move rule"),
+            DrawReason::SeventyFiveMoves => write!(f, "seventy-five-move rule"),
+            DrawReason::ThreefoldRepetition => write!(f, "threefold repetition"),
+            DrawReason::FivefoldRepetition => write!(f, "fivefold repetition"),
+            DrawReason::InsufficientMaterial => write!(f, "insufficient material"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum GameStatus {
+    Ongoing,
+    /// The side to move is in check and has no legal move.
+    Checkmate { winner: Player },
+    /// The side to move is not in check but has no legal move.
+    Stalemate,
+    /// The game ended in a draw without either player asking for it.
+    Draw(DrawReason),
+    /// The game goes on, but the side to move may claim a draw.
+    DrawClaimable(DrawReason),
+}
+
+impl GameStatus {
+    pub fn is_over(&self) -> bool {
+        !matches!(self, GameStatus::Ongoing | GameStatus::DrawClaimable(_))
+    }
+
+    /// The result as written in PGN, e.g. `1-0`, or `*` while the game goes on.
+    pub fn result(&self) -> &'static str {
+        match self {
+            GameStatus::Ongoing | GameStatus::DrawClaimable(_) => "*",
+            GameStatus::Checkmate { winner: Player::White } => "1-0",
+            GameStatus::Checkmate { winner: Player::Black } => "0-1",
+            GameStatus::Stalemate | GameStatus::Draw(_) => "1/2-1/2",
+        }
+    }
+}
+
+impl fmt::Display for GameStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameStatus::Ongoing => write!(f, "game in progress"),
+            GameStatus::Checkmate { winner: Player::White } => write!(f, "checkmate, white wins"),
+            GameStatus::Checkmate { winner: Player::Black } => write!(f, "checkmate, black wins"),
+            GameStatus::Stalemate => write!(f, "stalemate, draw"),
+            GameStatus::Draw(reason) => write!(f, "draw by {}", reason),
+            GameStatus::DrawClaimable(reason) => write!(f, "game in progress, draw can be claimed by {}", reason),
+        }
+    }
+}
+
+impl ChessBoard {
+    pub fn status(&self) -> GameStatus {
+        if self.legal_moves().is_empty() {
+            return if self.in_check(self.turn) {
+                GameStatus::Checkmate {
+                    winner: self.turn.opponent(),
+                }
+            } else {
+                GameStatus::Stalemate
+            };
+        }
+        if self.is_insufficient_material() {
+            return GameStatus::Draw(DrawReason::InsufficientMaterial);
+        }
+        let repetitions = self.repetitions();
+        if repetitions >= 5 {
+            GameStatus::Draw(DrawReason::FivefoldRepetition)
+        } else if self.halfmove_clock >= 150 {
+            GameStatus::Draw(DrawReason::SeventyFiveMoves)
+        } else if repetitions >= 3 {
+            GameStatus::DrawClaimable(DrawReason::ThreefoldRepetition)
+        } else if self.halfmove_clock >= 100 {
+            GameStatus::DrawClaimable(DrawReason::FiftyMoves)
+        } else {
+            GameStatus::Ongoing
+        }
+    }
+
+    /// How many times the current position has occurred, counting this occurrence. Only positions
+    /// since the last capture or pawn move can repeat.
+    pub(crate) fn repetitions(&self) -> usize {
+        let since_irreversible = (self.halfmove_clock as usize).min(self.history.len());
+        let earlier = self.history[self.history.len() - since_irreversible..]
+            .iter()
+            .filter(|&&previous| previous == self.hash)
+            .count();
+        earlier + 1
+    }
+
+    /// King against king, king and a single minor piece against king, or kings with bishops that
+    /// all stand on squares of the same colour.
+    pub(crate) fn is_insufficient_material(&self) -> bool {
+        let mut minors = 0;
+        let mut knights = 0;
+        let mut bishop_colours = [false, false];
+        for (square, value) in self.board.iter().enumerate() {
+            let chess_piece = match value.value {
+                Some(chess_piece) => chess_piece,
+                None => continue,
+            };
+            match chess_piece.piece {
+                Piece::King => (),
+                Piece::Knight => {
+                    minors += 1;
+                    knights += 1;
+                }
+                Piece::Bishop => {
+                    minors += 1;
+                    bishop_colours[(get_row(square) + get_col(square)) % 2] = true;
+                }
+                Piece::Pawn | Piece::Rook | Piece::Queen => return false,
+            }
+        }
+        minors <= 1 || (knights == 0 && !(bishop_colours[0] && bishop_colours[1]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChessMove;
+
+    fn status(fen: &str) -> GameStatus {
+        ChessBoard::from_fen(fen).unwrap().status()
+    }
+
+    #[test]
+    fn checkmate_and_stalemate() {
+        assert_eq!(
+            status("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3"),
+            GameStatus::Checkmate { winner: Player::Black }
+        );
+        assert_eq!(status("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"), GameStatus::Stalemate);
+        assert_eq!(status("7k/8/6K1/8/8/8/8/5Q2 b - - 0 1"), GameStatus::Ongoing);
+    }
+
+    #[test]
+    fn move_rules() {
+        assert_eq!(status("4k3/8/8/8/8/8/8/R3K3 w - - 99 80"), GameStatus::Ongoing);
+        assert_eq!(
+            status("4k3/8/8/8/8/8/8/R3K3 w - - 100 80"),
+            GameStatus::DrawClaimable(DrawReason::FiftyMoves)
+        );
+        assert_eq!(
+            status("4k3/8/8/8/8/8/8/R3K3 w - - 150 80"),
+            GameStatus::Draw(DrawReason::SeventyFiveMoves)
+        );
+        // Checkmate on the move that reaches the limit still wins.
+        assert_eq!(
+            status("R3k3/8/4K3/8/8/8/8/8 b - - 150 80"),
+            GameStatus::Checkmate { winner: Player::White }
+        );
+    }
+
+    #[test]
+    fn repetition() {
+        let mut chess_board = ChessBoard::new();
+        let shuffle = [ChessMove::new(6, 21), ChessMove::new(62, 45), ChessMove::new(21, 6), ChessMove::new(45, 62)];
+        let mut statuses = Vec::new();
+        for _ in 0..4 {
+            for &chess_move in &shuffle {
+                chess_board.make_move(chess_move);
+            }
+            statuses.push(chess_board.status());
+        }
+        assert_eq!(
+            statuses,
+            vec![
+                GameStatus::Ongoing,
+                GameStatus::DrawClaimable(DrawReason::ThreefoldRepetition),
+                GameStatus::DrawClaimable(DrawReason::ThreefoldRepetition),
+                GameStatus::Draw(DrawReason::FivefoldRepetition),
+            ]
+        );
+    }
+
+    #[test]
+    fn insufficient_material() {
+        let draw = GameStatus::Draw(DrawReason::InsufficientMaterial);
+        assert_eq!(status("4k3/8/8/8/8/8/8/4K3 w - - 0 1"), draw);
+        assert_eq!(status("4k3/8/8/8/8/8/8/4KN2 w - - 0 1"), draw);
+        assert_eq!(status("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1"), draw);
+        assert_eq!(status("4k1b1/8/8/8/8/8/8/2B1K3 w - - 0 1"), GameStatus::Ongoing);
+        assert_eq!(status("4k3/8/8/8/8/8/8/3NKN2 w - - 0 1"), GameStatus::Ongoing);
+        assert_eq!(status("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"), GameStatus::Ongoing);
+    }
+}
@@ -0,0 +1,227 @@
+use std::fmt::Write;
+
+use crate::{get_col, get_row, square_name, ChessBoard, ChessMove, Piece, Player};
+
+const SQUARE: usize = 45;
+const MARGIN: usize = 20;
+const LIGHT: &str = "#f0d9b5";
+const DARK: &str = "#b58863";
+const HIGHLIGHT: &str = "#cdd26a";
+const CHECK: &str = "#e0443e";
+const MARK: &str = "#15781b";
+
+/// An SVG diagram of a board. Built with `ChessBoard::svg`; pieces are drawn as text so the
+/// image needs nothing beyond a font with the Unicode chess symbols.
+#[derive(Debug, Clone)]
+pub struct SvgDiagram<'a> {
+    chess_board: &'a ChessBoard,
+    orientation: Player,
+    coordinates: bool,
+    last_move: Option<ChessMove>,
+    arrows: Vec<(usize, usize)>,
+    circles: Vec<usize>,
+}
+
+impl<'a> SvgDiagram<'a> {
+    /// The side whose pieces are drawn at the bottom.
+    pub fn orientation(mut self, player: Player) -> Self {
+        self.orientation = player;
+        self
+    }
+
+    /// Whether to label the ranks and files around the board.
+    pub fn coordinates(mut self, coordinates: bool) -> Self {
+        self.coordinates = coordinates;
+        self
+    }
+
+    /// Highlights the squares the move went from and to.
+    pub fn last_move(mut self, chess_move: Option<ChessMove>) -> Self {
+        self.last_move = chess_move;
+        self
+    }
+
+    /// Draws an arrow between two squares. Squares off the board (64 and up) are ignored.
+    pub fn arrow(mut self, from: usize, to: usize) -> Self {
+        if from < 64 && to < 64 {
+            self.arrows.push((from, to));
+        }
+        self
+    }
+
+    /// Circles a square. Squares off the board (64 and up) are ignored.
+    pub fn circle(mut self, square: usize) -> Self {
+        if square < 64 {
+            self.circles.push(square);
+        }
+        self
+    }
+
+    /// The top left corner of a square in image coordinates.
+    fn corner(&self, square: usize) -> (usize, usize) {
+        let margin = if self.coordinates { MARGIN } else { 0 };
+        let (col, row) = match self.orientation {
+            Player::White => (get_col(square), 7 - get_row(square)),
+            Player::Black => (7 - get_col(square), get_row(square)),
+        };
+        (margin + col * SQUARE, margin + row * SQUARE)
+    }
+
+    fn centre(&self, square: usize) -> (f64, f64) {
+        let (x, y) = self.corner(square);
+        ((x + SQUARE / 2) as f64 + 0.5, (y + SQUARE / 2) as f64 + 0.5)
+    }
+
+    pub fn to_svg(&self) -> String {
+        let size = 8 * SQUARE + if self.coordinates { 2 * MARGIN } else { 0 };
+        let mut svg = String::new();
+        // Writing to a String cannot fail, so the results are ignored throughout.
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#,
+            size
+        );
+        let _ = writeln!(
+            svg,
+            r#"<defs><marker id="arrowhead" markerWidth="4" markerHeight="4" refX="2" refY="2" orient="auto"><path d="M0,0 L4,2 L0,4 Z" fill="{}"/></marker></defs>"#,
+            MARK
+        );
+        if self.coordinates {
+            let _ = writeln!(svg, r##"<rect width="{0}" height="{0}" fill="#212121"/>"##, size);
+        }
+
+        let turn = self.chess_board.turn;
+        let checked_king = if self.chess_board.in_check(turn) { self.chess_board.king_square(turn) } else { None };
+        for square in 0..64 {
+            let (x, y) = self.corner(square);
+            let light = (get_row(square) + get_col(square)) % 2 == 1;
+            let fill = if checked_king == Some(square) {
+                CHECK
+            } else if self.last_move.is_some_and(|chess_move| chess_move.from == square || chess_move.to == square) {
+                HIGHLIGHT
+            } else if light {
+                LIGHT
+            } else {
+                DARK
+            };
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                x, y, SQUARE, SQUARE, fill
+            );
+        }
+
+        if self.coordinates {
+            for i in 0..8 {
+                let name = square_name(i);
+                let (x, _) = self.corner(i);
+                let (_, y) = self.corner(i * 8);
+                let margin = MARGIN as f64;
+                let bottom = (MARGIN + 8 * SQUARE) as f64 + margin * 0.7;
+                let middle = (SQUARE / 2) as f64;
+                let _ = writeln!(
+                    svg,
+                    r##"<text x="{}" y="{}" font-family="sans-serif" font-size="14" fill="#e5e5e5" text-anchor="middle">{}</text>"##,
+                    x as f64 + middle,
+                    bottom,
+                    &name[..1]
+                );
+                let _ = writeln!(
+                    svg,
+                    r##"<text x="{}" y="{}" font-family="sans-serif" font-size="14" fill="#e5e5e5" text-anchor="middle" dominant-baseline="central">{}</text>"##,
+                    margin / 2.0,
+                    y as f64 + middle,
+                    i + 1
+                );
+            }
+        }
+
+        for (square, value) in self.chess_board.board.iter().enumerate() {
+            let chess_piece = match value.value {
+                Some(chess_piece) => chess_piece,
+                None => continue,
+            };
+            let (x, y) = self.centre(square);
+            let (fill, stroke) = match chess_piece.player {
+                Player::White => ("#ffffff", "#000000"),
+                Player::Black => ("#000000", "#000000"),
+            };
+            // The filled black symbols are used for both sides and coloured in. The variation
+            // selector keeps the pawn from being shown as an emoji.
+            let glyph = match chess_piece.piece {
+                Piece::King => "\u{265a}",
+                Piece::Queen => "\u{265b}",
+                Piece::Rook => "\u{265c}",
+                Piece::Bishop => "\u{265d}",
+                Piece::Knight => "\u{265e}",
+                Piece::Pawn => "\u{265f}\u{fe0e}",
+            };
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{}" font-family="DejaVu Sans, Segoe UI Symbol, sans-serif" font-size="38" fill="{}" stroke="{}" stroke-width="1.2" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                x, y, fill, stroke, glyph
+            );
+        }
+
+        for &square in &self.circles {
+            let (x, y) = self.centre(square);
+            let _ = writeln!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="none" stroke="{}" stroke-width="4" opacity="0.8"/>"#,
+                x,
+                y,
+                SQUARE as f64 / 2.0 - 3.0,
+                MARK
+            );
+        }
+
+        for &(from, to) in &self.arrows {
+            let (x1, y1) = self.centre(from);
+            let (x2, y2) = self.centre(to);
+            // Stop short of the centre by the arrowhead's overhang so its tip lands on the centre.
+            let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt().max(1.0);
+            let shorten = SQUARE as f64 * 0.4;
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{}" y1="{}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-width="9" stroke-linecap="round" opacity="0.8" marker-end="url(#arrowhead)"/>"#,
+                x1,
+                y1,
+                x2 - (x2 - x1) * shorten / length,
+                y2 - (y2 - y1) * shorten / length,
+                MARK
+            );
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+impl ChessBoard {
+    /// An SVG diagram from White's side with coordinates, to be adjusted with the `SvgDiagram`
+    /// setters.
+    pub fn svg(&self) -> SvgDiagram<'_> {
+        SvgDiagram {
+            chess_board: self,
+            orientation: Player::White,
+            coordinates: true,
+            last_move: None,
+            arrows: Vec::new(),
+            circles: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_marks_off_the_board() {
+        let chess_board = ChessBoard::new();
+        let plain = chess_board.svg().to_svg();
+        assert_eq!(chess_board.svg().arrow(12, 64).arrow(usize::MAX, 28).circle(64).to_svg(), plain);
+        assert_ne!(chess_board.svg().arrow(12, 28).to_svg(), plain);
+        assert_ne!(chess_board.svg().circle(63).to_svg(), plain);
+    }
+}
@@ -0,0 +1,195 @@
+use std::fmt;
+use std::mem;
+
+use crate::search::MATE_BOUND;
+use crate::{ChessMove, Piece};
+
+/// The table size a `Searcher` gets unless told otherwise.
+pub const DEFAULT_HASH_MB: usize = 16;
+
+/// The largest table size; larger requests are capped to it.
+pub const MAX_HASH_MB: usize = 64 * 1024;
+
+/// What a stored score says about the true score of its position.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub(crate) enum Bound {
+    Exact,
+    /// The search failed high: the true score is at least this.
+    Lower,
+    /// The search failed low: the true score is at most this.
+    Upper,
+}
+
+/// The result of searching one position, as handed back by a probe.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Entry {
+    pub(crate) depth: u32,
+    pub(crate) bound: Bound,
+    pub(crate) score: i32,
+    pub(crate) best_move: Option<ChessMove>,
+}
+
+/// One slot of the table. The move is packed into 16 bits to keep slots small.
+#[derive(Debug, Default, Copy, Clone)]
+struct Slot {
+    key: u64,
+    score: i32,
+    chess_move: u16,
+    depth: u8,
+    /// Zero for an empty slot, otherwise 1 + `Bound as u8`.
+    bound: u8,
+    /// The search the slot was written in, so that entries left over from earlier searches are
+    /// replaced first.
+    generation: u8,
+}
+
+/// A hash table of searched positions keyed by Zobrist key, so that a position reached again
+/// through a different move order is not searched again.
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+    generation: u8,
+}
+
+impl TranspositionTable {
+    /// A table taking up at most `megabytes`, capped at `MAX_HASH_MB`, rounded down to a power of
+    /// two number of slots.
+    pub fn new(megabytes: usize) -> Self {
+        let bytes = megabytes.min(MAX_HASH_MB).saturating_mul(1024 * 1024);
+        let wanted = (bytes / mem::size_of::<Slot>()).max(1);
+        let len = 1 << (usize::BITS - 1 - wanted.leading_zeros());
+        TranspositionTable {
+            slots: vec![Slot::default(); len],
+            generation: 0,
+        }
+    }
+
+    /// Forgets every stored position.
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = Slot::default());
+        self.generation = 0;
+    }
+
+    /// Marks the start of a new search; entries from before it are replaced first.
+    pub(crate) fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    fn index(&self, key: u64) -> usize {
+        key as usize & (self.slots.len() - 1)
+    }
+
+    /// The stored result for the position with Zobrist key `key` reached `ply` plies from the root.
+    pub(crate) fn probe(&self, key: u64, ply: usize) -> Option<Entry> {
+        let slot = &self.slots[self.index(key)];
+        if slot.bound == 0 || slot.key != key {
+            return None;
+        }
+        let bound = match slot.bound {
+            1 => Bound::Exact,
+            2 => Bound::Lower,
+            _ => Bound::Upper,
+        };
+        Some(Entry {
+            depth: slot.depth as u32,
+            bound,
+            score: score_from_table(slot.score, ply),
+            best_move: unpack_move(slot.chess_move),
+        })
+    }
+
+    /// Stores a search result. A slot holding another position is only given up if it comes from
+    /// an earlier search or was searched less deeply.
+    pub(crate) fn store(&mut self, key: u64, ply: usize, entry: Entry) {
+        let generation = self.generation;
+        let index = self.index(key);
+        let slot = &mut self.slots[index];
+        if slot.bound != 0 && slot.key != key && slot.generation == generation && slot.depth as u32 > entry.depth {
+            return;
+        }
+        // A failed-low search has no best move, so keep the one found before.
+        let chess_move = match entry.best_move {
+            Some(chess_move) => pack_move(chess_move),
+            None if slot.key == key => slot.chess_move,
+            None => 0,
+        };
+        *slot = Slot {
+            key,
+            score: score_to_table(entry.score, ply),
+            chess_move,
+            depth: entry.depth.min(u8::MAX as u32) as u8,
+            bound: 1 + entry.bound as u8,
+            generation,
+        };
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        TranspositionTable::new(DEFAULT_HASH_MB)
+    }
+}
+
+impl fmt::Debug for TranspositionTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TranspositionTable")
+            .field("slots", &self.slots.len())
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+/// Mate scores count plies from the root, but a stored position can be reached at any ply, so they
+/// are stored counting from the position itself instead.
+fn score_to_table(score: i32, ply: usize) -> i32 {
+    if score > MATE_BOUND {
+        score + ply as i32
+    } else if score < -MATE_BOUND {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_table(score: i32, ply: usize) -> i32 {
+    if score > MATE_BOUND {
+        score - ply as i32
+    } else if score < -MATE_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+/// Six bits each for the squares and three for the promotion piece. Zero stands for no move, as
+/// a1 to a1 is never one.
+fn pack_move(chess_move: ChessMove) -> u16 {
+    let promotion = match chess_move.promotion {
+        None => 0,
+        Some(Piece::Queen) => 1,
+        Some(Piece::Rook) => 2,
+        Some(Piece::Bishop) => 3,
+        Some(Piece::Knight) => 4,
+        Some(Piece::King) | Some(Piece::Pawn) => unreachable!("pawns never promote to a king or a pawn"),
+    };
+    (chess_move.from | chess_move.to << 6 | promotion << 12) as u16
+}
+
+fn unpack_move(packed: u16) -> Option<ChessMove> {
+    if packed == 0 {
+        return None;
+    }
+    let packed = packed as usize;
+    let promotion = match packed >> 12 {
+        1 => Some(Piece::Queen),
+        2 => Some(Piece::Rook),
+        3 => Some(Piece::Bishop),
+        4 => Some(Piece::Knight),
+        _ => None,
+    };
+    Some(ChessMove {
+        from: packed & 63,
+        to: packed >> 6 & 63,
+        promotion,
+    })
+}
+
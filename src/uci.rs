@@ -0,0 +1,81 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::{parse_square, square_name, ChessMove, Piece};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum UciError {
+    /// A UCI move is two squares optionally followed by a promotion letter, e.g. `e7e8q`.
+    Length(String),
+    InvalidSquare(String),
+    InvalidPromotion(char),
+}
+
+impl fmt::Display for UciError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UciError::Length(text) => write!(f, "'{}' is not shaped like e2e4 or e7e8q", text),
+            UciError::InvalidSquare(square) => write!(f, "'{}' is not a square", square),
+            UciError::InvalidPromotion(letter) => write!(f, "cannot promote to '{}', expected q, r, b or n", letter),
+        }
+    }
+}
+
+impl Error for UciError {}
+
+impl ChessMove {
+    /// Reads a move in UCI long algebraic notation such as `e2e4` or `e7e8q`, in either case.
+    /// Castling is written as the king's move, e.g. `e1g1`.
+    pub fn from_uci(text: &str) -> Result<ChessMove, UciError> {
+        if !text.is_ascii() || (text.len() != 4 && text.len() != 5) {
+            return Err(UciError::Length(text.to_string()));
+        }
+        let from = parse_square(&text[0..2]).ok_or_else(|| UciError::InvalidSquare(text[0..2].to_string()))?;
+        let to = parse_square(&text[2..4]).ok_or_else(|| UciError::InvalidSquare(text[2..4].to_string()))?;
+        let promotion = match text[4..].chars().next() {
+            Some(letter) => match Piece::from_letter(letter.to_ascii_uppercase()) {
+                Some(piece) if Piece::PROMOTIONS.contains(&piece) => Some(piece),
+                _ => return Err(UciError::InvalidPromotion(letter)),
+            },
+            None => None,
+        };
+        Ok(ChessMove { from, to, promotion })
+    }
+
+    pub fn to_uci(&self) -> String {
+        let mut uci = square_name(self.from) + &square_name(self.to);
+        if let Some(piece) = self.promotion {
+            uci.push(piece.letter().to_ascii_lowercase());
+        }
+        uci
+    }
+}
+
+impl fmt::Display for ChessMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_uci())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for text in &["e2e4", "e1g1", "e7e8q", "a2a1n"] {
+            assert_eq!(ChessMove::from_uci(text).unwrap().to_uci(), *text);
+        }
+        assert_eq!(ChessMove::from_uci("E7E8Q").unwrap().to_uci(), "e7e8q");
+    }
+
+    #[test]
+    fn rejects_malformed_moves() {
+        assert_eq!(ChessMove::from_uci("e2e"), Err(UciError::Length("e2e".to_string())));
+        assert_eq!(ChessMove::from_uci("e2e4qq"), Err(UciError::Length("e2e4qq".to_string())));
+        assert_eq!(ChessMove::from_uci("e2é4"), Err(UciError::Length("e2é4".to_string())));
+        assert_eq!(ChessMove::from_uci("e9e4"), Err(UciError::InvalidSquare("e9".to_string())));
+        assert_eq!(ChessMove::from_uci("e2i4"), Err(UciError::InvalidSquare("i4".to_string())));
+        assert_eq!(ChessMove::from_uci("e7e8k"), Err(UciError::InvalidPromotion('k')));
+    }
+}